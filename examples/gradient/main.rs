@@ -19,7 +19,7 @@ async fn main() -> anyhow::Result<()> {
         extensions: vec![
             deno_webidl::deno_webidl::init(),
             deno_web::deno_web::init(blob_store.clone(), None, true, Default::default()),
-            canvas_2d::canvas_2d::init(),
+            canvas_2d::canvas_2d::init(Default::default(), Default::default()),
             init::init(),
         ],
         ..Default::default()
@@ -10,6 +10,9 @@ export {
   op_canvas_2d_font_face_id,
   op_canvas_2d_font_face_line_gap_override,
   op_canvas_2d_font_face_load,
+  op_canvas_2d_font_face_load_local,
+  op_canvas_2d_font_face_named_instance,
+  op_canvas_2d_font_face_named_instances,
   op_canvas_2d_font_face_new,
   op_canvas_2d_font_face_select_source,
   op_canvas_2d_font_face_set_ascent_override,
@@ -21,15 +24,22 @@ export {
   op_canvas_2d_font_face_set_insert,
   op_canvas_2d_font_face_set_line_gap_override,
   op_canvas_2d_font_face_set_match,
+  op_canvas_2d_font_face_set_named_instance,
   op_canvas_2d_font_face_set_remove,
+  op_canvas_2d_font_face_set_set_generic_family,
+  op_canvas_2d_font_face_set_size_adjust,
   op_canvas_2d_font_face_set_stretch,
   op_canvas_2d_font_face_set_style,
+  op_canvas_2d_font_face_set_synthesis,
   op_canvas_2d_font_face_set_unicode_range,
   op_canvas_2d_font_face_set_variation_settings,
   op_canvas_2d_font_face_set_weight,
+  op_canvas_2d_font_face_size_adjust,
   op_canvas_2d_font_face_stretch,
   op_canvas_2d_font_face_style,
+  op_canvas_2d_font_face_synthesis,
   op_canvas_2d_font_face_unicode_range,
+  op_canvas_2d_font_face_variation_axes,
   op_canvas_2d_font_face_variation_settings,
   op_canvas_2d_font_face_weight,
   op_canvas_2d_font_source,
@@ -52,20 +62,33 @@ export {
   op_canvas_2d_image_bitmap_resize,
   op_canvas_2d_image_bitmap_width,
   op_canvas_2d_path_arc_to,
+  op_canvas_2d_path_boolean_op,
   op_canvas_2d_path_clear,
   op_canvas_2d_path_clone,
   op_canvas_2d_path_close,
   op_canvas_2d_path_cubic_to,
+  op_canvas_2d_path_dash,
   op_canvas_2d_path_ellipse,
   op_canvas_2d_path_ensure_subpath,
   op_canvas_2d_path_extend,
+  op_canvas_2d_path_flatten,
+  op_canvas_2d_path_from_bytes,
   op_canvas_2d_path_from_svg,
+  op_canvas_2d_path_get_control_bounds,
+  op_canvas_2d_path_get_length,
+  op_canvas_2d_path_get_point_at_length,
+  op_canvas_2d_path_get_subpath_count,
+  op_canvas_2d_path_get_tight_bounds,
   op_canvas_2d_path_line_to,
   op_canvas_2d_path_move_to,
   op_canvas_2d_path_new,
+  op_canvas_2d_path_offset,
   op_canvas_2d_path_quad_to,
   op_canvas_2d_path_rect,
   op_canvas_2d_path_round_rect,
+  op_canvas_2d_path_simplify,
+  op_canvas_2d_path_to_bytes,
+  op_canvas_2d_path_to_svg,
   op_canvas_2d_pattern_new,
   op_canvas_2d_pattern_set_transform,
   op_canvas_2d_state_clear,
@@ -78,11 +101,20 @@ export {
   op_canvas_2d_state_fill_rect,
   op_canvas_2d_state_fill_style,
   op_canvas_2d_state_fill_text,
+  op_canvas_2d_state_fill_text_layout,
+  op_canvas_2d_state_fill_text_on_path,
   op_canvas_2d_state_font,
+  op_canvas_2d_state_font_feature_settings,
   op_canvas_2d_state_font_kerning,
   op_canvas_2d_state_font_stretch,
   op_canvas_2d_state_font_variant_caps,
+  op_canvas_2d_state_font_variant_ligatures,
+  op_canvas_2d_state_font_variant_numeric,
+  op_canvas_2d_state_font_variation_settings,
+  op_canvas_2d_state_get_device_clip_bounds,
   op_canvas_2d_state_get_image_data,
+  op_canvas_2d_state_get_inverse_transform,
+  op_canvas_2d_state_get_local_clip_bounds,
   op_canvas_2d_state_get_transform,
   op_canvas_2d_state_global_alpha,
   op_canvas_2d_state_global_composite_operation,
@@ -91,6 +123,8 @@ export {
   op_canvas_2d_state_image_smoothing_quality,
   op_canvas_2d_state_is_point_in_path,
   op_canvas_2d_state_is_point_in_stroke,
+  op_canvas_2d_state_is_rect_clipped_out,
+  op_canvas_2d_state_layout_text,
   op_canvas_2d_state_letter_spacing,
   op_canvas_2d_state_line_cap,
   op_canvas_2d_state_line_dash_offset,
@@ -113,9 +147,13 @@ export {
   op_canvas_2d_state_set_fill_style_pattern,
   op_canvas_2d_state_set_filter,
   op_canvas_2d_state_set_font,
+  op_canvas_2d_state_set_font_feature_settings,
   op_canvas_2d_state_set_font_kerning,
   op_canvas_2d_state_set_font_stretch,
   op_canvas_2d_state_set_font_variant_caps,
+  op_canvas_2d_state_set_font_variant_ligatures,
+  op_canvas_2d_state_set_font_variant_numeric,
+  op_canvas_2d_state_set_font_variation_settings,
   op_canvas_2d_state_set_global_alpha,
   op_canvas_2d_state_set_global_composite_operation,
   op_canvas_2d_state_set_height,
@@ -149,11 +187,24 @@ export {
   op_canvas_2d_state_stroke_rect,
   op_canvas_2d_state_stroke_style,
   op_canvas_2d_state_stroke_text,
+  op_canvas_2d_state_stroke_text_layout,
+  op_canvas_2d_state_stroke_text_on_path,
+  op_canvas_2d_state_stroke_to_path,
   op_canvas_2d_state_text_align,
   op_canvas_2d_state_text_baseline,
   op_canvas_2d_state_text_rendering,
+  op_canvas_2d_state_text_to_path,
   op_canvas_2d_state_transform,
   op_canvas_2d_state_translate,
   op_canvas_2d_state_width,
   op_canvas_2d_state_word_spacing,
+  op_canvas_2d_text_layout_get_line,
+  op_canvas_2d_text_layout_get_line_count,
+  op_canvas_2d_text_layout_get_metrics,
+  op_canvas_2d_text_layout_to_path,
+  op_canvas_2d_text_metrics_get_actual_bounding_box,
+  op_canvas_2d_text_metrics_get_glyph,
+  op_canvas_2d_text_metrics_get_glyph_count,
+  op_canvas_2d_text_metrics_get_index_from_offset,
+  op_canvas_2d_text_metrics_get_selection_rects,
 } from "ext:core/ops";
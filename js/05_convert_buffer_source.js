@@ -0,0 +1,21 @@
+import { primordials } from "ext:core/mod.js";
+import { isArrayBuffer } from "./02_is_array_buffer.js";
+import { isDataView } from "./02_is_data_view.js";
+import { isTypedArray } from "./02_is_typed_array.js";
+import { convertArrayBuffer } from "./05_convert_array_buffer.js";
+import { convertDataView } from "./05_convert_data_view.js";
+import { convertTypedArray } from "./05_convert_typed_array.js";
+
+const { TypeError } = primordials;
+export const convertBufferSource = (value) => {
+  if (isArrayBuffer(value)) {
+    return convertArrayBuffer(value);
+  }
+  if (isDataView(value)) {
+    return convertDataView(value);
+  }
+  if (isTypedArray(value)) {
+    return convertTypedArray(value);
+  }
+  throw new TypeError("Expected BufferSource");
+};
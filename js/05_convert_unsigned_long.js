@@ -0,0 +1 @@
+export const convertUnsignedLong = (value) => value >>> 0;
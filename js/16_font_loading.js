@@ -9,6 +9,9 @@ import {
   op_canvas_2d_font_face_id,
   op_canvas_2d_font_face_line_gap_override,
   op_canvas_2d_font_face_load,
+  op_canvas_2d_font_face_load_local,
+  op_canvas_2d_font_face_named_instance,
+  op_canvas_2d_font_face_named_instances,
   op_canvas_2d_font_face_new,
   op_canvas_2d_font_face_select_source,
   op_canvas_2d_font_face_set_ascent_override,
@@ -20,15 +23,22 @@ import {
   op_canvas_2d_font_face_set_insert,
   op_canvas_2d_font_face_set_line_gap_override,
   op_canvas_2d_font_face_set_match,
+  op_canvas_2d_font_face_set_named_instance,
   op_canvas_2d_font_face_set_remove,
+  op_canvas_2d_font_face_set_set_generic_family,
+  op_canvas_2d_font_face_set_size_adjust,
   op_canvas_2d_font_face_set_stretch,
   op_canvas_2d_font_face_set_style,
+  op_canvas_2d_font_face_set_synthesis,
   op_canvas_2d_font_face_set_unicode_range,
   op_canvas_2d_font_face_set_variation_settings,
   op_canvas_2d_font_face_set_weight,
+  op_canvas_2d_font_face_size_adjust,
   op_canvas_2d_font_face_stretch,
   op_canvas_2d_font_face_style,
+  op_canvas_2d_font_face_synthesis,
   op_canvas_2d_font_face_unicode_range,
+  op_canvas_2d_font_face_variation_axes,
   op_canvas_2d_font_face_variation_settings,
   op_canvas_2d_font_face_weight,
   op_canvas_2d_font_source,
@@ -47,11 +57,10 @@ import { isTypedArray } from "./02_is_typed_array.js";
 import { requireObject } from "./02_require_object.js";
 import { createDictionaryConverter } from "./04_create_dictionary_converter.js";
 import { createSequenceFromIterable } from "./04_create_sequence_from_iterable.js";
-import { convertArrayBuffer } from "./05_convert_array_buffer.js";
-import { convertDataView } from "./05_convert_data_view.js";
+import { convertBufferSource } from "./05_convert_buffer_source.js";
 import { convertDOMString } from "./05_convert_dom_string.js";
+import { convertEnforceRangeUnsignedLong } from "./05_convert_enforce_range_unsigned_long.js";
 import { convertEventHandler } from "./05_convert_event_handler.js";
-import { convertTypedArray } from "./05_convert_typed_array.js";
 import { EventHandler, readEventInitMembers } from "./15_event.js";
 
 const {
@@ -102,18 +111,10 @@ const empty = ObjectFreeze({
   next: () => ({ done: true }),
   [SymbolIterator]: () => empty,
 });
-const convertDOMStringOrBufferSource = (value) => {
-  if (isArrayBuffer(value)) {
-    return convertArrayBuffer(value);
-  }
-  if (isDataView(value)) {
-    return convertDataView(value);
-  }
-  if (isTypedArray(value)) {
-    return convertTypedArray(value);
-  }
-  return convertDOMString(value);
-};
+const convertDOMStringOrBufferSource = (value) =>
+  isArrayBuffer(value) || isDataView(value) || isTypedArray(value)
+    ? convertBufferSource(value)
+    : convertDOMString(value);
 const readFontFaceDescriptorsMembers = (value) => {
   const result = { __proto__: null };
   const { style = "normal" } = value;
@@ -136,6 +137,14 @@ const readFontFaceDescriptorsMembers = (value) => {
   result.descentOverride = convertDOMString(descentOverride);
   const { lineGapOverride = "normal" } = value;
   result.lineGapOverride = convertDOMString(lineGapOverride);
+  const { sizeAdjust = "100%" } = value;
+  result.sizeAdjust = convertDOMString(sizeAdjust);
+  const { synthesis = "weight style small-caps position" } = value;
+  result.synthesis = convertDOMString(synthesis);
+  const { namedInstance = "auto" } = value;
+  result.namedInstance = convertDOMString(namedInstance);
+  const { faceIndex = 0 } = value;
+  result.faceIndex = convertEnforceRangeUnsignedLong(faceIndex);
   return result;
 };
 const convertFontFaceDescriptors = createDictionaryConverter(
@@ -146,6 +155,7 @@ const FontFaceInternals = class FontFace extends IdentityConstructor {
 
   #raw;
   #url;
+  #local = false;
   #status = "unloaded";
   #innerLoaded = new Deferred();
   #loaded = newFromSpeciesSafePromise(
@@ -163,6 +173,9 @@ const FontFaceInternals = class FontFace extends IdentityConstructor {
   #cachedAscentOverride = null;
   #cachedDescentOverride = null;
   #cachedLineGapOverride = null;
+  #cachedSizeAdjust = null;
+  #cachedSynthesis = null;
+  #cachedNamedInstance = null;
 
   constructor(
     o,
@@ -178,12 +191,13 @@ const FontFaceInternals = class FontFace extends IdentityConstructor {
     ascentOverride,
     descentOverride,
     lineGapOverride,
+    sizeAdjust,
+    synthesis,
+    namedInstance,
+    faceIndex,
   ) {
     super(o);
     try {
-      this.#url = typeof source === "string"
-        ? op_canvas_2d_font_face_select_source(source)
-        : null;
       this.#raw = op_canvas_2d_font_face_new(
         family,
         style,
@@ -196,7 +210,15 @@ const FontFaceInternals = class FontFace extends IdentityConstructor {
         ascentOverride,
         descentOverride,
         lineGapOverride,
+        sizeAdjust,
+        synthesis,
+        namedInstance,
+        faceIndex,
       );
+      this.#url = typeof source === "string"
+        ? op_canvas_2d_font_face_select_source(this.#raw, source)
+        : null;
+      this.#local = typeof source === "string" && this.#url === null;
     } catch (e) {
       FontFaceInternals.setError(this, e);
       this.#url = null;
@@ -359,6 +381,36 @@ const FontFaceInternals = class FontFace extends IdentityConstructor {
     o.#cachedLineGapOverride = null;
   }
 
+  static getSizeAdjust(o) {
+    o.#cachedSizeAdjust = op_canvas_2d_font_face_size_adjust(o.#raw);
+    return o.#cachedSizeAdjust;
+  }
+
+  static setSizeAdjust(o, value) {
+    op_canvas_2d_font_face_set_size_adjust(o.#raw, value);
+    o.#cachedSizeAdjust = null;
+  }
+
+  static getSynthesis(o) {
+    o.#cachedSynthesis = op_canvas_2d_font_face_synthesis(o.#raw);
+    return o.#cachedSynthesis;
+  }
+
+  static setSynthesis(o, value) {
+    op_canvas_2d_font_face_set_synthesis(o.#raw, value);
+    o.#cachedSynthesis = null;
+  }
+
+  static getNamedInstance(o) {
+    o.#cachedNamedInstance = op_canvas_2d_font_face_named_instance(o.#raw);
+    return o.#cachedNamedInstance;
+  }
+
+  static setNamedInstance(o, value) {
+    op_canvas_2d_font_face_set_named_instance(o.#raw, value);
+    o.#cachedNamedInstance = null;
+  }
+
   static setLoading(o) {
     o.#status = "loading";
     // deno-lint-ignore prefer-primordials
@@ -405,6 +457,18 @@ const FontFaceInternals = class FontFace extends IdentityConstructor {
         }
         FontFaceInternals.setLoaded(o);
       });
+    } else if (o.#local) {
+      o.#local = false;
+      FontFaceInternals.setLoading(o);
+      defer(() => {
+        try {
+          op_canvas_2d_font_face_load_local(o.#raw);
+        } catch (e) {
+          FontFaceInternals.setError(o, e);
+          return;
+        }
+        FontFaceInternals.setLoaded(o);
+      });
     }
     return o.#innerLoaded.promise;
   }
@@ -426,6 +490,9 @@ const FontFaceInternals = class FontFace extends IdentityConstructor {
           "ascentOverride",
           "descentOverride",
           "lineGapOverride",
+          "sizeAdjust",
+          "synthesis",
+          "namedInstance",
           "status",
           "loaded",
         ],
@@ -458,6 +525,10 @@ export class FontFace extends Object {
       descriptors.ascentOverride,
       descriptors.descentOverride,
       descriptors.lineGapOverride,
+      descriptors.sizeAdjust,
+      descriptors.synthesis,
+      descriptors.namedInstance,
+      descriptors.faceIndex,
     );
     return o;
   }
@@ -583,6 +654,39 @@ export class FontFace extends Object {
     FontFaceInternals.setLineGapOverride(this, value);
   }
 
+  get sizeAdjust() {
+    FontFaceInternals.checkInstance(this);
+    return FontFaceInternals.getSizeAdjust(this);
+  }
+
+  set sizeAdjust(value) {
+    FontFaceInternals.checkInstance(this);
+    value = convertDOMString(value);
+    FontFaceInternals.setSizeAdjust(this, value);
+  }
+
+  get synthesis() {
+    FontFaceInternals.checkInstance(this);
+    return FontFaceInternals.getSynthesis(this);
+  }
+
+  set synthesis(value) {
+    FontFaceInternals.checkInstance(this);
+    value = convertDOMString(value);
+    FontFaceInternals.setSynthesis(this, value);
+  }
+
+  get namedInstance() {
+    FontFaceInternals.checkInstance(this);
+    return FontFaceInternals.getNamedInstance(this);
+  }
+
+  set namedInstance(value) {
+    FontFaceInternals.checkInstance(this);
+    value = convertDOMString(value);
+    FontFaceInternals.setNamedInstance(this, value);
+  }
+
   get status() {
     return FontFaceInternals.getStatus(this);
   }
@@ -1058,6 +1162,38 @@ export function setFetchFont(fn) {
   fetchFont = fn;
 }
 
+export function getFontFaceVariations(font) {
+  font = convertFontFace(font);
+  const raw = FontFaceInternals.getRaw(font);
+  const axisValues = op_canvas_2d_font_face_variation_axes(raw);
+  const axes = [];
+  for (let i = 0; i < axisValues.length; i += 6) {
+    ArrayPrototypePush(axes, {
+      tag: axisValues[i],
+      name: axisValues[i + 1],
+      min: axisValues[i + 2],
+      default: axisValues[i + 3],
+      max: axisValues[i + 4],
+      hidden: axisValues[i + 5],
+    });
+  }
+  const instanceValues = op_canvas_2d_font_face_named_instances(raw);
+  const namedInstances = [];
+  for (let i = 0; i < instanceValues.length; i += 3) {
+    const coordinates = {};
+    const values = instanceValues[i + 2];
+    for (let j = 0; j < axes.length; j++) {
+      coordinates[axes[j].tag] = values[j];
+    }
+    ArrayPrototypePush(namedInstances, {
+      name: instanceValues[i],
+      postscriptName: instanceValues[i + 1],
+      coordinates,
+    });
+  }
+  return { axes, namedInstances };
+}
+
 let fonts;
 export const { "get fonts": getFonts } = {
   "get fonts"() {
@@ -1068,3 +1204,15 @@ export const { "get fonts": getFonts } = {
     return fonts;
   },
 };
+
+export function setGenericFontFamily(generic, families) {
+  const prefix = "Failed to execute 'setGenericFontFamily'";
+  requiredArguments(arguments.length, 2, prefix);
+  generic = convertDOMString(generic);
+  families = convertDOMString(families);
+  op_canvas_2d_font_face_set_set_generic_family(
+    op_canvas_2d_font_source(),
+    generic,
+    families,
+  );
+}
@@ -22,20 +22,33 @@ import {
   op_canvas_2d_image_bitmap_height,
   op_canvas_2d_image_bitmap_width,
   op_canvas_2d_path_arc_to,
+  op_canvas_2d_path_boolean_op,
   op_canvas_2d_path_clear,
   op_canvas_2d_path_clone,
   op_canvas_2d_path_close,
   op_canvas_2d_path_cubic_to,
+  op_canvas_2d_path_dash,
   op_canvas_2d_path_ellipse,
   op_canvas_2d_path_ensure_subpath,
   op_canvas_2d_path_extend,
+  op_canvas_2d_path_flatten,
+  op_canvas_2d_path_from_bytes,
   op_canvas_2d_path_from_svg,
+  op_canvas_2d_path_get_control_bounds,
+  op_canvas_2d_path_get_length,
+  op_canvas_2d_path_get_point_at_length,
+  op_canvas_2d_path_get_subpath_count,
+  op_canvas_2d_path_get_tight_bounds,
   op_canvas_2d_path_line_to,
   op_canvas_2d_path_move_to,
   op_canvas_2d_path_new,
+  op_canvas_2d_path_offset,
   op_canvas_2d_path_quad_to,
   op_canvas_2d_path_rect,
   op_canvas_2d_path_round_rect,
+  op_canvas_2d_path_simplify,
+  op_canvas_2d_path_to_bytes,
+  op_canvas_2d_path_to_svg,
   op_canvas_2d_pattern_new,
   op_canvas_2d_pattern_set_transform,
   op_canvas_2d_state_clear,
@@ -48,11 +61,20 @@ import {
   op_canvas_2d_state_fill_rect,
   op_canvas_2d_state_fill_style,
   op_canvas_2d_state_fill_text,
+  op_canvas_2d_state_fill_text_layout,
+  op_canvas_2d_state_fill_text_on_path,
   op_canvas_2d_state_font,
+  op_canvas_2d_state_font_feature_settings,
   op_canvas_2d_state_font_kerning,
   op_canvas_2d_state_font_stretch,
   op_canvas_2d_state_font_variant_caps,
+  op_canvas_2d_state_font_variant_ligatures,
+  op_canvas_2d_state_font_variant_numeric,
+  op_canvas_2d_state_font_variation_settings,
+  op_canvas_2d_state_get_device_clip_bounds,
   op_canvas_2d_state_get_image_data,
+  op_canvas_2d_state_get_inverse_transform,
+  op_canvas_2d_state_get_local_clip_bounds,
   op_canvas_2d_state_get_transform,
   op_canvas_2d_state_global_alpha,
   op_canvas_2d_state_global_composite_operation,
@@ -61,6 +83,8 @@ import {
   op_canvas_2d_state_image_smoothing_quality,
   op_canvas_2d_state_is_point_in_path,
   op_canvas_2d_state_is_point_in_stroke,
+  op_canvas_2d_state_is_rect_clipped_out,
+  op_canvas_2d_state_layout_text,
   op_canvas_2d_state_letter_spacing,
   op_canvas_2d_state_line_cap,
   op_canvas_2d_state_line_dash_offset,
@@ -83,9 +107,13 @@ import {
   op_canvas_2d_state_set_fill_style_pattern,
   op_canvas_2d_state_set_filter,
   op_canvas_2d_state_set_font,
+  op_canvas_2d_state_set_font_feature_settings,
   op_canvas_2d_state_set_font_kerning,
   op_canvas_2d_state_set_font_stretch,
   op_canvas_2d_state_set_font_variant_caps,
+  op_canvas_2d_state_set_font_variant_ligatures,
+  op_canvas_2d_state_set_font_variant_numeric,
+  op_canvas_2d_state_set_font_variation_settings,
   op_canvas_2d_state_set_global_alpha,
   op_canvas_2d_state_set_global_composite_operation,
   op_canvas_2d_state_set_height,
@@ -119,13 +147,26 @@ import {
   op_canvas_2d_state_stroke_rect,
   op_canvas_2d_state_stroke_style,
   op_canvas_2d_state_stroke_text,
+  op_canvas_2d_state_stroke_text_layout,
+  op_canvas_2d_state_stroke_text_on_path,
+  op_canvas_2d_state_stroke_to_path,
   op_canvas_2d_state_text_align,
   op_canvas_2d_state_text_baseline,
   op_canvas_2d_state_text_rendering,
+  op_canvas_2d_state_text_to_path,
   op_canvas_2d_state_transform,
   op_canvas_2d_state_translate,
   op_canvas_2d_state_width,
   op_canvas_2d_state_word_spacing,
+  op_canvas_2d_text_layout_get_line,
+  op_canvas_2d_text_layout_get_line_count,
+  op_canvas_2d_text_layout_get_metrics,
+  op_canvas_2d_text_layout_to_path,
+  op_canvas_2d_text_metrics_get_actual_bounding_box,
+  op_canvas_2d_text_metrics_get_glyph,
+  op_canvas_2d_text_metrics_get_glyph_count,
+  op_canvas_2d_text_metrics_get_index_from_offset,
+  op_canvas_2d_text_metrics_get_selection_rects,
 } from "./00_ops.js";
 import { defaultTo } from "./01_default_to.js";
 import { IdentityConstructor } from "./01_identity_constructor.js";
@@ -136,13 +177,16 @@ import { createDictionaryConverter } from "./04_create_dictionary_converter.js";
 import { createEnumConverter } from "./04_create_enum_converter.js";
 import { createSequenceFromIterable } from "./04_create_sequence_from_iterable.js";
 import { convertBoolean } from "./05_convert_boolean.js";
+import { convertBufferSource } from "./05_convert_buffer_source.js";
 import { convertDOMString } from "./05_convert_dom_string.js";
 import { convertDouble } from "./05_convert_double.js";
 import { convertEnforceRangeLong } from "./05_convert_enforce_range_long.js";
+import { convertEnforceRangeUnsignedLong } from "./05_convert_enforce_range_unsigned_long.js";
 import { convertImageData } from "./05_convert_image_data.js";
 import { convertLegacyNullToEmptyStringDOMString } from "./05_convert_legacy_null_to_empty_string_dom_string.js";
 import { convertPredefinedColorSpace } from "./05_convert_predefined_color_space.js";
 import { convertUnrestrictedDouble } from "./05_convert_unrestricted_double.js";
+import { convertUnsignedLong } from "./05_convert_unsigned_long.js";
 import {
   convertDOMMatrix2DInit,
   convertDOMPointInit,
@@ -384,9 +428,13 @@ const pattern = (image, repetition) => {
     op_canvas_2d_pattern_new(bitmap, repetition),
   );
 };
+const textMetricsBuffer = new Float64Array(4);
 const TextMetricsInternals = class TextMetrics extends IdentityConstructor {
   #brand() {}
 
+  #raw;
+  #length;
+  #glyphs = null;
   #width;
   #actualBoundingBoxLeft;
   #actualBoundingBoxRight;
@@ -399,9 +447,14 @@ const TextMetricsInternals = class TextMetrics extends IdentityConstructor {
   #hangingBaseline;
   #alphabeticBaseline;
   #ideographicBaseline;
+  #fontLineGap;
+  #capHeight;
+  #xHeight;
 
-  constructor(o, values) {
+  constructor(o, raw, length, values) {
     super(o);
+    this.#raw = raw;
+    this.#length = length;
     this.#width = values[0];
     this.#actualBoundingBoxLeft = values[1];
     this.#actualBoundingBoxRight = values[2];
@@ -414,6 +467,9 @@ const TextMetricsInternals = class TextMetrics extends IdentityConstructor {
     this.#hangingBaseline = values[9];
     this.#alphabeticBaseline = values[10];
     this.#ideographicBaseline = values[11];
+    this.#fontLineGap = values[12];
+    this.#capHeight = values[13];
+    this.#xHeight = values[14];
   }
 
   static hasInstance(o) {
@@ -473,6 +529,84 @@ const TextMetricsInternals = class TextMetrics extends IdentityConstructor {
     return o.#ideographicBaseline;
   }
 
+  static getFontLineGap(o) {
+    return o.#fontLineGap;
+  }
+
+  static getCapHeight(o) {
+    return o.#capHeight;
+  }
+
+  static getXHeight(o) {
+    return o.#xHeight;
+  }
+
+  static getGlyphs(o) {
+    if (o.#glyphs === null) {
+      const glyphs = [];
+      const count = op_canvas_2d_text_metrics_get_glyph_count(o.#raw);
+      for (let i = 0; i < count; i++) {
+        op_canvas_2d_text_metrics_get_glyph(o.#raw, i, textMetricsBuffer);
+        ArrayPrototypePush(
+          glyphs,
+          ObjectFreeze({
+            index: textMetricsBuffer[0],
+            x: textMetricsBuffer[1],
+            advance: textMetricsBuffer[2],
+          }),
+        );
+      }
+      o.#glyphs = ObjectFreeze(glyphs);
+    }
+    return o.#glyphs;
+  }
+
+  static checkRange(o, start, end) {
+    if (start > end || end > o.#length) {
+      throw new DOMException("Index is out of range", "IndexSizeError");
+    }
+  }
+
+  static getSelectionRects(o, start, end) {
+    const values = op_canvas_2d_text_metrics_get_selection_rects(
+      o.#raw,
+      start,
+      end,
+    );
+    const rects = [];
+    for (let i = 0; i < values.length; i += 4) {
+      ArrayPrototypePush(
+        rects,
+        ObjectFreeze({
+          x: values[i],
+          y: values[i + 1],
+          width: values[i + 2],
+          height: values[i + 3],
+        }),
+      );
+    }
+    return rects;
+  }
+
+  static getActualBoundingBox(o, start, end) {
+    op_canvas_2d_text_metrics_get_actual_bounding_box(
+      o.#raw,
+      start,
+      end,
+      textMetricsBuffer,
+    );
+    return ObjectFreeze({
+      x: textMetricsBuffer[0],
+      y: textMetricsBuffer[1],
+      width: textMetricsBuffer[2],
+      height: textMetricsBuffer[3],
+    });
+  }
+
+  static getIndexFromOffset(o, offset) {
+    return op_canvas_2d_text_metrics_get_index_from_offset(o.#raw, offset);
+  }
+
   static inspect(inspect, options) {
     return inspect(
       createFilteredInspectProxy({
@@ -491,6 +625,10 @@ const TextMetricsInternals = class TextMetrics extends IdentityConstructor {
           "hangingBaseline",
           "alphabeticBaseline",
           "ideographicBaseline",
+          "fontLineGap",
+          "capHeight",
+          "xHeight",
+          "glyphs",
         ],
       }),
       options,
@@ -552,6 +690,50 @@ export class TextMetrics extends Object {
     return TextMetricsInternals.getIdeographicBaseline(this);
   }
 
+  get fontLineGap() {
+    return TextMetricsInternals.getFontLineGap(this);
+  }
+
+  get capHeight() {
+    return TextMetricsInternals.getCapHeight(this);
+  }
+
+  get xHeight() {
+    return TextMetricsInternals.getXHeight(this);
+  }
+
+  get glyphs() {
+    return TextMetricsInternals.getGlyphs(this);
+  }
+
+  getSelectionRects(start, end) {
+    TextMetricsInternals.checkInstance(this);
+    const prefix = "Failed to execute 'getSelectionRects' on 'TextMetrics'";
+    requiredArguments(arguments.length, 2, prefix);
+    start = convertUnsignedLong(start);
+    end = convertUnsignedLong(end);
+    TextMetricsInternals.checkRange(this, start, end);
+    return TextMetricsInternals.getSelectionRects(this, start, end);
+  }
+
+  getActualBoundingBox(start, end) {
+    TextMetricsInternals.checkInstance(this);
+    const prefix = "Failed to execute 'getActualBoundingBox' on 'TextMetrics'";
+    requiredArguments(arguments.length, 2, prefix);
+    start = convertUnsignedLong(start);
+    end = convertUnsignedLong(end);
+    TextMetricsInternals.checkRange(this, start, end);
+    return TextMetricsInternals.getActualBoundingBox(this, start, end);
+  }
+
+  getIndexFromOffset(offset) {
+    TextMetricsInternals.checkInstance(this);
+    const prefix = "Failed to execute 'getIndexFromOffset' on 'TextMetrics'";
+    requiredArguments(arguments.length, 1, prefix);
+    offset = convertDouble(offset);
+    return TextMetricsInternals.getIndexFromOffset(this, offset);
+  }
+
   get [privateCustomInspect]() {
     return TextMetricsInternals.hasInstance(this)
       ? TextMetricsInternals.inspect
@@ -564,9 +746,139 @@ export class TextMetrics extends Object {
   }
 }
 
-function createTextMetrics(values) {
+function createTextMetricsFromRaw(raw, length, values) {
   const o = ObjectCreate(TextMetrics.prototype);
-  new TextMetricsInternals(o, values);
+  new TextMetricsInternals(o, raw, length, values);
+  return o;
+}
+
+const textLayoutBuffer = new Float64Array(5);
+const TextLayoutInternals = class TextLayout extends IdentityConstructor {
+  #brand() {}
+
+  #raw;
+  #width;
+  #height;
+  #truncated;
+  #lines;
+
+  constructor(o, raw) {
+    super(o);
+    this.#raw = raw;
+    this.#truncated = op_canvas_2d_text_layout_get_metrics(
+      raw,
+      textLayoutBuffer,
+    );
+    this.#width = textLayoutBuffer[0];
+    this.#height = textLayoutBuffer[1];
+    const lines = [];
+    const count = op_canvas_2d_text_layout_get_line_count(raw);
+    for (let i = 0; i < count; i++) {
+      op_canvas_2d_text_layout_get_line(raw, i, textLayoutBuffer);
+      ArrayPrototypePush(
+        lines,
+        ObjectFreeze({
+          start: textLayoutBuffer[0],
+          end: textLayoutBuffer[1],
+          x: textLayoutBuffer[2],
+          baseline: textLayoutBuffer[3],
+          width: textLayoutBuffer[4],
+        }),
+      );
+    }
+    this.#lines = ObjectFreeze(lines);
+  }
+
+  static hasInstance(o) {
+    // deno-lint-ignore prefer-primordials
+    return #brand in o;
+  }
+
+  static checkInstance(o) {
+    o.#brand;
+  }
+
+  static getRaw(o) {
+    return o.#raw;
+  }
+
+  static getWidth(o) {
+    return o.#width;
+  }
+
+  static getHeight(o) {
+    return o.#height;
+  }
+
+  static getTruncated(o) {
+    return o.#truncated;
+  }
+
+  static getLines(o) {
+    return o.#lines;
+  }
+
+  static inspect(inspect, options) {
+    return inspect(
+      createFilteredInspectProxy({
+        object: this,
+        evaluate: true,
+        keys: [
+          "width",
+          "height",
+          "truncated",
+          "lines",
+        ],
+      }),
+      options,
+    );
+  }
+};
+
+export class TextLayout extends Object {
+  // deno-lint-ignore constructor-super
+  constructor() {
+    illegalConstructor();
+  }
+
+  get width() {
+    return TextLayoutInternals.getWidth(this);
+  }
+
+  get height() {
+    return TextLayoutInternals.getHeight(this);
+  }
+
+  get truncated() {
+    return TextLayoutInternals.getTruncated(this);
+  }
+
+  get lines() {
+    return TextLayoutInternals.getLines(this);
+  }
+
+  toPath() {
+    TextLayoutInternals.checkInstance(this);
+    return createPath2DFromRaw(
+      op_canvas_2d_text_layout_to_path(TextLayoutInternals.getRaw(this)),
+    );
+  }
+
+  get [privateCustomInspect]() {
+    return TextLayoutInternals.hasInstance(this)
+      ? TextLayoutInternals.inspect
+      : undefined;
+  }
+
+  static {
+    ObjectSetPrototypeOf(this, FunctionPrototype);
+    configureInterface(this);
+  }
+}
+
+function createTextLayoutFromRaw(raw) {
+  const o = ObjectCreate(TextLayout.prototype);
+  new TextLayoutInternals(o, raw);
   return o;
 }
 
@@ -587,10 +899,29 @@ const convertPath2D = (value) => {
   }
   throw new TypeError("Expected Path2D");
 };
+const convertTextLayout = (value) => {
+  if (isObject(value) && TextLayoutInternals.hasInstance(value)) {
+    return value;
+  }
+  throw new TypeError("Expected TextLayout");
+};
 const convertPath2DOrDOMString = (value) =>
   isObject(value) && Path2DInternals.hasInstance(value)
     ? value
     : convertDOMString(value);
+const readPath2DSVGOptionsMembers = (value) => {
+  const result = { __proto__: null };
+  const { precision } = value;
+  if (precision !== undefined) {
+    result.precision = convertEnforceRangeUnsignedLong(precision);
+  }
+  const { relative = false } = value;
+  result.relative = convertBoolean(relative);
+  return result;
+};
+const convertPath2DSVGOptions = createDictionaryConverter(
+  readPath2DSVGOptionsMembers,
+);
 
 function normalizeAndScaleRadii(x, y, w, h, radii) {
   if (
@@ -701,6 +1032,8 @@ const Path2DInternals = class Path2D extends IdentityConstructor {
   }
 };
 
+const pathQueryBuffer = new Float64Array(4);
+
 export class Path2D extends Object {
   constructor(path = undefined) {
     if (path !== undefined) {
@@ -941,12 +1274,213 @@ export class Path2D extends Object {
     );
   }
 
+  getTightBounds() {
+    Path2DInternals.checkInstance(this);
+    if (
+      !op_canvas_2d_path_get_tight_bounds(
+        Path2DInternals.getRaw(this),
+        pathQueryBuffer,
+      )
+    ) {
+      return null;
+    }
+    const { 0: x, 1: y, 2: width, 3: height } = pathQueryBuffer;
+    return { x, y, width, height };
+  }
+
+  getControlBounds() {
+    Path2DInternals.checkInstance(this);
+    if (
+      !op_canvas_2d_path_get_control_bounds(
+        Path2DInternals.getRaw(this),
+        pathQueryBuffer,
+      )
+    ) {
+      return null;
+    }
+    const { 0: x, 1: y, 2: width, 3: height } = pathQueryBuffer;
+    return { x, y, width, height };
+  }
+
+  getTotalLength() {
+    Path2DInternals.checkInstance(this);
+    return op_canvas_2d_path_get_length(Path2DInternals.getRaw(this));
+  }
+
+  getPointAtLength(distance) {
+    Path2DInternals.checkInstance(this);
+    const prefix = "Failed to execute 'getPointAtLength' on 'Path2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    distance = convertUnrestrictedDouble(distance);
+    if (
+      !op_canvas_2d_path_get_point_at_length(
+        Path2DInternals.getRaw(this),
+        distance,
+        pathQueryBuffer,
+      )
+    ) {
+      return null;
+    }
+    const { 0: x, 1: y } = pathQueryBuffer;
+    return { x, y };
+  }
+
+  getTangentAtLength(distance) {
+    Path2DInternals.checkInstance(this);
+    const prefix = "Failed to execute 'getTangentAtLength' on 'Path2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    distance = convertUnrestrictedDouble(distance);
+    if (
+      !op_canvas_2d_path_get_point_at_length(
+        Path2DInternals.getRaw(this),
+        distance,
+        pathQueryBuffer,
+      )
+    ) {
+      return null;
+    }
+    const { 2: x, 3: y } = pathQueryBuffer;
+    return { x, y };
+  }
+
+  getSubpathCount() {
+    Path2DInternals.checkInstance(this);
+    return op_canvas_2d_path_get_subpath_count(Path2DInternals.getRaw(this));
+  }
+
+  toSVGPathData(options = undefined) {
+    Path2DInternals.checkInstance(this);
+    options = convertPath2DSVGOptions(options);
+    const { precision } = options;
+    return op_canvas_2d_path_to_svg(
+      Path2DInternals.getRaw(this),
+      precision === undefined ? -1 : MathMin(precision, 20),
+      options.relative,
+    );
+  }
+
+  toBinary() {
+    Path2DInternals.checkInstance(this);
+    return op_canvas_2d_path_to_bytes(Path2DInternals.getRaw(this));
+  }
+
+  static fromBinary(data) {
+    const prefix = "Failed to execute 'fromBinary' on 'Path2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    data = convertBufferSource(data);
+    return createPath2DFromRaw(op_canvas_2d_path_from_bytes(data));
+  }
+
+  flatten(tolerance = 0.25) {
+    Path2DInternals.checkInstance(this);
+    tolerance = convertUnrestrictedDouble(tolerance);
+    checkTolerance(tolerance);
+    return createPath2DFromRaw(
+      op_canvas_2d_path_flatten(Path2DInternals.getRaw(this), tolerance),
+    );
+  }
+
+  simplify(tolerance = 0.25) {
+    Path2DInternals.checkInstance(this);
+    tolerance = convertUnrestrictedDouble(tolerance);
+    checkTolerance(tolerance);
+    return createPath2DFromRaw(
+      op_canvas_2d_path_simplify(Path2DInternals.getRaw(this), tolerance),
+    );
+  }
+
+  offset(
+    distance,
+    fillRule = "nonzero",
+    tolerance = 0.25,
+    lineJoin = "round",
+    miterLimit = 10,
+  ) {
+    Path2DInternals.checkInstance(this);
+    const prefix = "Failed to execute 'offset' on 'Path2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    distance = convertUnrestrictedDouble(distance);
+    fillRule = convertCanvasFillRule(fillRule);
+    tolerance = convertUnrestrictedDouble(tolerance);
+    lineJoin = convertCanvasLineJoin(lineJoin);
+    miterLimit = convertUnrestrictedDouble(miterLimit);
+    checkTolerance(tolerance);
+    return createPath2DFromRaw(
+      op_canvas_2d_path_offset(
+        Path2DInternals.getRaw(this),
+        distance,
+        fillRuleToRepr[fillRule],
+        lineJoinToRepr[lineJoin],
+        NumberIsFinite(miterLimit) && miterLimit > 0 ? miterLimit : 10,
+        tolerance,
+      ),
+    );
+  }
+
+  union(path, fillRule = "nonzero", otherFillRule = "nonzero") {
+    Path2DInternals.checkInstance(this);
+    const prefix = "Failed to execute 'union' on 'Path2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    return combinePaths(this, path, fillRule, otherFillRule, 0);
+  }
+
+  intersect(path, fillRule = "nonzero", otherFillRule = "nonzero") {
+    Path2DInternals.checkInstance(this);
+    const prefix = "Failed to execute 'intersect' on 'Path2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    return combinePaths(this, path, fillRule, otherFillRule, 1);
+  }
+
+  difference(path, fillRule = "nonzero", otherFillRule = "nonzero") {
+    Path2DInternals.checkInstance(this);
+    const prefix = "Failed to execute 'difference' on 'Path2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    return combinePaths(this, path, fillRule, otherFillRule, 2);
+  }
+
+  xor(path, fillRule = "nonzero", otherFillRule = "nonzero") {
+    Path2DInternals.checkInstance(this);
+    const prefix = "Failed to execute 'xor' on 'Path2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    return combinePaths(this, path, fillRule, otherFillRule, 3);
+  }
+
   static {
     ObjectSetPrototypeOf(this, FunctionPrototype);
     configureInterface(this);
   }
 }
 
+function checkTolerance(tolerance) {
+  if (!(NumberIsFinite(tolerance) && tolerance > 0)) {
+    throw new DOMException(
+      "Tolerance must be positive and finite",
+      "IndexSizeError",
+    );
+  }
+}
+
+function createPath2DFromRaw(raw) {
+  const o = ObjectCreate(Path2D.prototype);
+  new Path2DInternals(o, raw);
+  return o;
+}
+
+function combinePaths(path, other, fillRule, otherFillRule, operation) {
+  other = convertPath2D(other);
+  fillRule = convertCanvasFillRule(fillRule);
+  otherFillRule = convertCanvasFillRule(otherFillRule);
+  return createPath2DFromRaw(
+    op_canvas_2d_path_boolean_op(
+      Path2DInternals.getRaw(path),
+      fillRuleToRepr[fillRule],
+      Path2DInternals.getRaw(other),
+      fillRuleToRepr[otherFillRule],
+      operation,
+    ),
+  );
+}
+
 const convertSequenceOfUnrestrictedDouble = (value) => {
   const method = requireObject(value)[SymbolIterator];
   return createSequenceFromIterable(value, method, convertUnrestrictedDouble);
@@ -968,6 +1502,121 @@ const convertUnrestrictedDoubleOrDOMPointInitOrSequenceThereof = (value) => {
   }
   return convertUnrestrictedDoubleOrDOMPointInit(value);
 };
+const convertCanvasLineCap = createEnumConverter(
+  "CanvasLineCap",
+  ["butt", "round", "square"],
+);
+const convertCanvasLineJoin = createEnumConverter(
+  "CanvasLineJoin",
+  ["round", "bevel", "miter"],
+);
+const readCanvasStrokeStyleInitMembers = (value) => {
+  const result = { __proto__: null };
+  const { lineCap } = value;
+  if (lineCap !== undefined) {
+    result.lineCap = convertCanvasLineCap(lineCap);
+  }
+  const { lineDash } = value;
+  if (lineDash !== undefined) {
+    result.lineDash = convertSequenceOfUnrestrictedDouble(lineDash);
+  }
+  const { lineDashOffset } = value;
+  if (lineDashOffset !== undefined) {
+    result.lineDashOffset = convertUnrestrictedDouble(lineDashOffset);
+  }
+  const { lineJoin } = value;
+  if (lineJoin !== undefined) {
+    result.lineJoin = convertCanvasLineJoin(lineJoin);
+  }
+  const { lineWidth } = value;
+  if (lineWidth !== undefined) {
+    result.lineWidth = convertUnrestrictedDouble(lineWidth);
+  }
+  const { miterLimit } = value;
+  if (miterLimit !== undefined) {
+    result.miterLimit = convertUnrestrictedDouble(miterLimit);
+  }
+  return result;
+};
+const convertCanvasStrokeStyleInit = createDictionaryConverter(
+  readCanvasStrokeStyleInitMembers,
+);
+const convertCanvasTextPathSide = createEnumConverter(
+  "CanvasTextPathSide",
+  ["left", "right"],
+);
+const readCanvasTextOnPathOptionsMembers = (value) => {
+  const result = { __proto__: null };
+  const { maxWidth } = value;
+  if (maxWidth !== undefined) {
+    result.maxWidth = convertUnrestrictedDouble(maxWidth);
+  }
+  const { side = "left" } = value;
+  result.side = convertCanvasTextPathSide(side);
+  const { startOffset = 0 } = value;
+  result.startOffset = convertUnrestrictedDouble(startOffset);
+  return result;
+};
+const convertCanvasTextOnPathOptions = createDictionaryConverter(
+  readCanvasTextOnPathOptionsMembers,
+);
+const convertCanvasTextLayoutAlign = createEnumConverter(
+  "CanvasTextLayoutAlign",
+  ["start", "end", "left", "right", "center", "justify"],
+);
+const readCanvasTextLayoutOptionsMembers = (value) => {
+  const result = { __proto__: null };
+  const { align = "start" } = value;
+  result.align = convertCanvasTextLayoutAlign(align);
+  const { ellipsis = "\u2026" } = value;
+  result.ellipsis = convertDOMString(ellipsis);
+  const { lineHeight } = value;
+  if (lineHeight !== undefined) {
+    result.lineHeight = convertUnrestrictedDouble(lineHeight);
+  }
+  const { maxLines } = value;
+  if (maxLines !== undefined) {
+    result.maxLines = convertEnforceRangeUnsignedLong(maxLines);
+  }
+  const { maxWidth = Infinity } = value;
+  result.maxWidth = convertUnrestrictedDouble(maxWidth);
+  return result;
+};
+const convertCanvasTextLayoutOptions = createDictionaryConverter(
+  readCanvasTextLayoutOptionsMembers,
+);
+
+function resolveLineDash(state, segments) {
+  if (segments !== undefined) {
+    const count = segments.length;
+    const buf = new Float64Array(count);
+    let i = 0;
+    for (; i < count; i++) {
+      const value = segments[i];
+      if (!(NumberIsFinite(value) && value >= 0)) {
+        break;
+      }
+      buf[i] = value;
+    }
+    if (i === count) {
+      return buf;
+    }
+  }
+  segments = op_canvas_2d_state_dash_list(state);
+  const count = segments.length;
+  const buf = new Float64Array(count);
+  for (let i = 0; i < count; i++) {
+    buf[i] = segments[i];
+  }
+  return buf;
+}
+
+function resolveLineDashOffset(state, value) {
+  return value !== undefined && NumberIsFinite(value)
+    ? value
+    : op_canvas_2d_state_line_dash_offset(state);
+}
+
 const OffscreenCanvasRenderingContext2DInternals =
   class OffscreenCanvasRenderingContext2D extends IdentityConstructor {
     #brand() {}
@@ -983,6 +1632,10 @@ const OffscreenCanvasRenderingContext2DInternals =
     #cachedFont = null;
     #cachedLetterSpacing = null;
     #cachedWordSpacing = null;
+    #cachedFontVariantNumeric = null;
+    #cachedFontVariantLigatures = null;
+    #cachedFontFeatureSettings = null;
+    #cachedFontVariationSettings = null;
     #cachedFillStyle = null;
     #cachedStrokeStyle = null;
     #cachedDefaultPath = null;
@@ -1044,6 +1697,10 @@ const OffscreenCanvasRenderingContext2DInternals =
         font: o.#cachedFont,
         letterSpacing: o.#cachedLetterSpacing,
         wordSpacing: o.#cachedWordSpacing,
+        fontVariantNumeric: o.#cachedFontVariantNumeric,
+        fontVariantLigatures: o.#cachedFontVariantLigatures,
+        fontFeatureSettings: o.#cachedFontFeatureSettings,
+        fontVariationSettings: o.#cachedFontVariationSettings,
         fillStyle: o.#cachedFillStyle,
         strokeStyle: o.#cachedStrokeStyle,
         defaultPath: o.#cachedDefaultPath,
@@ -1062,6 +1719,10 @@ const OffscreenCanvasRenderingContext2DInternals =
       o.#cachedFont = cache.font;
       o.#cachedLetterSpacing = cache.letterSpacing;
       o.#cachedWordSpacing = cache.wordSpacing;
+      o.#cachedFontVariantNumeric = cache.fontVariantNumeric;
+      o.#cachedFontVariantLigatures = cache.fontVariantLigatures;
+      o.#cachedFontFeatureSettings = cache.fontFeatureSettings;
+      o.#cachedFontVariationSettings = cache.fontVariationSettings;
       o.#cachedFillStyle = cache.fillStyle;
       o.#cachedStrokeStyle = cache.strokeStyle;
       o.#cachedDefaultPath = cache.defaultPath;
@@ -1189,6 +1850,8 @@ const OffscreenCanvasRenderingContext2DInternals =
     static setFont(o, value) {
       if (op_canvas_2d_state_set_font(o.#state, value)) {
         o.#cachedFont = null;
+        o.#cachedFontVariantNumeric = null;
+        o.#cachedFontVariantLigatures = null;
       }
     }
 
@@ -1218,6 +1881,50 @@ const OffscreenCanvasRenderingContext2DInternals =
       }
     }
 
+    static getFontVariantNumeric(o) {
+      o.#cachedFontVariantNumeric ??= op_canvas_2d_state_font_variant_numeric(o.#state);
+      return o.#cachedFontVariantNumeric;
+    }
+
+    static setFontVariantNumeric(o, value) {
+      if (op_canvas_2d_state_set_font_variant_numeric(o.#state, value)) {
+        o.#cachedFontVariantNumeric = null;
+      }
+    }
+
+    static getFontVariantLigatures(o) {
+      o.#cachedFontVariantLigatures ??= op_canvas_2d_state_font_variant_ligatures(o.#state);
+      return o.#cachedFontVariantLigatures;
+    }
+
+    static setFontVariantLigatures(o, value) {
+      if (op_canvas_2d_state_set_font_variant_ligatures(o.#state, value)) {
+        o.#cachedFontVariantLigatures = null;
+      }
+    }
+
+    static getFontFeatureSettings(o) {
+      o.#cachedFontFeatureSettings ??= op_canvas_2d_state_font_feature_settings(o.#state);
+      return o.#cachedFontFeatureSettings;
+    }
+
+    static setFontFeatureSettings(o, value) {
+      if (op_canvas_2d_state_set_font_feature_settings(o.#state, value)) {
+        o.#cachedFontFeatureSettings = null;
+      }
+    }
+
+    static getFontVariationSettings(o) {
+      o.#cachedFontVariationSettings ??= op_canvas_2d_state_font_variation_settings(o.#state);
+      return o.#cachedFontVariationSettings;
+    }
+
+    static setFontVariationSettings(o, value) {
+      if (op_canvas_2d_state_set_font_variation_settings(o.#state, value)) {
+        o.#cachedFontVariationSettings = null;
+      }
+    }
+
     static inspect(inspect, options) {
       return inspect(
         createFilteredInspectProxy({
@@ -1249,6 +1956,10 @@ const OffscreenCanvasRenderingContext2DInternals =
             "fontKerning",
             "fontStretch",
             "fontVariantCaps",
+            "fontVariantNumeric",
+            "fontVariantLigatures",
+            "fontFeatureSettings",
+            "fontVariationSettings",
             "textRendering",
             "wordSpacing",
           ],
@@ -1388,6 +2099,20 @@ const textRenderingToRepr = ObjectFreeze({
   "optimizeLegibility": 2,
   "geometricPrecision": 3,
 });
+const textPathSideToRepr = ObjectFreeze({
+  __proto__: null,
+  "left": 0,
+  "right": 1,
+});
+const textLayoutAlignToRepr = ObjectFreeze({
+  __proto__: null,
+  "start": 0,
+  "end": 1,
+  "left": 2,
+  "right": 3,
+  "center": 4,
+  "justify": 5,
+});
 const fillRuleToRepr = ObjectFreeze({
   __proto__: null,
   "nonzero": 0,
@@ -1470,7 +2195,8 @@ const imageSmoothingQualityToRepr = ObjectFreeze({
   "high": 2,
 });
 const getTransformBuffer = new Float64Array(6);
-const measureTextBuffer = new Float64Array(12);
+const clipBoundsBuffer = new Float64Array(4);
+const measureTextBuffer = new Float64Array(15);
 
 export class OffscreenCanvasRenderingContext2D extends Object {
   // deno-lint-ignore constructor-super
@@ -1581,6 +2307,16 @@ export class OffscreenCanvasRenderingContext2D extends Object {
     return DOMMatrixFromFloat64Array(getTransformBuffer);
   }
 
+  getInverseTransform() {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    op_canvas_2d_state_get_inverse_transform(
+      OffscreenCanvasRenderingContext2DInternals.getState(this),
+      getTransformBuffer,
+    );
+    loadGeometry();
+    return DOMMatrixFromFloat64Array(getTransformBuffer);
+  }
+
   setTransform(a = undefined, b, c, d, e, f) {
     OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
     const nArgs = arguments.length;
@@ -1984,6 +2720,67 @@ export class OffscreenCanvasRenderingContext2D extends Object {
     );
   }
 
+  strokeToPath(path = undefined, style) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const nArgs = arguments.length;
+    if (
+      nArgs === 0 ||
+      (nArgs === 1 && !(isObject(path) && Path2DInternals.hasInstance(path)))
+    ) {
+      style = path;
+      path = null;
+    } else {
+      path = convertPath2D(path);
+    }
+    style = convertCanvasStrokeStyleInit(style);
+    const state = OffscreenCanvasRenderingContext2DInternals.getState(this);
+    const { lineCap, lineJoin, lineWidth, miterLimit } = style;
+    return createPath2DFromRaw(
+      op_canvas_2d_state_stroke_to_path(
+        state,
+        OffscreenCanvasRenderingContext2DInternals.getIntendedPath(this, path),
+        lineWidth !== undefined && NumberIsFinite(lineWidth) && lineWidth > 0
+          ? lineWidth
+          : op_canvas_2d_state_line_width(state),
+        lineCap !== undefined
+          ? lineCapToRepr[lineCap]
+          : op_canvas_2d_state_line_cap(state),
+        lineJoin !== undefined
+          ? lineJoinToRepr[lineJoin]
+          : op_canvas_2d_state_line_join(state),
+        miterLimit !== undefined && NumberIsFinite(miterLimit) &&
+          miterLimit > 0
+          ? miterLimit
+          : op_canvas_2d_state_miter_limit(state),
+        resolveLineDash(state, style.lineDash),
+        resolveLineDashOffset(state, style.lineDashOffset),
+      ),
+    );
+  }
+
+  dashToPath(path = undefined, style) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const nArgs = arguments.length;
+    if (
+      nArgs === 0 ||
+      (nArgs === 1 && !(isObject(path) && Path2DInternals.hasInstance(path)))
+    ) {
+      style = path;
+      path = null;
+    } else {
+      path = convertPath2D(path);
+    }
+    style = convertCanvasStrokeStyleInit(style);
+    const state = OffscreenCanvasRenderingContext2DInternals.getState(this);
+    return createPath2DFromRaw(
+      op_canvas_2d_path_dash(
+        OffscreenCanvasRenderingContext2DInternals.getIntendedPath(this, path),
+        resolveLineDash(state, style.lineDash),
+        resolveLineDashOffset(state, style.lineDashOffset),
+      ),
+    );
+  }
+
   clip(path = undefined, fillRule) {
     OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
     const nArgs = arguments.length;
@@ -2004,6 +2801,48 @@ export class OffscreenCanvasRenderingContext2D extends Object {
     );
   }
 
+  getDeviceClipBounds() {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    op_canvas_2d_state_get_device_clip_bounds(
+      OffscreenCanvasRenderingContext2DInternals.getState(this),
+      clipBoundsBuffer,
+    );
+    const { 0: x, 1: y, 2: width, 3: height } = clipBoundsBuffer;
+    return { x, y, width, height };
+  }
+
+  getLocalClipBounds() {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    if (
+      !op_canvas_2d_state_get_local_clip_bounds(
+        OffscreenCanvasRenderingContext2DInternals.getState(this),
+        clipBoundsBuffer,
+      )
+    ) {
+      return null;
+    }
+    const { 0: x, 1: y, 2: width, 3: height } = clipBoundsBuffer;
+    return { x, y, width, height };
+  }
+
+  isRectClippedOut(x, y, w, h) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to execute 'isRectClippedOut' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 4, prefix);
+    x = convertUnrestrictedDouble(x);
+    y = convertUnrestrictedDouble(y);
+    w = convertUnrestrictedDouble(w);
+    h = convertUnrestrictedDouble(h);
+    return op_canvas_2d_state_is_rect_clipped_out(
+      OffscreenCanvasRenderingContext2DInternals.getState(this),
+      x,
+      y,
+      w,
+      h,
+    );
+  }
+
   isPointInPath(path, x, y = undefined, fillRule) {
     OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
     const nArgs = arguments.length;
@@ -2106,18 +2945,170 @@ export class OffscreenCanvasRenderingContext2D extends Object {
     );
   }
 
+  fillTextOnPath(text, path, options = undefined) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to execute 'fillTextOnPath' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 2, prefix);
+    text = convertDOMString(text);
+    path = convertPath2D(path);
+    options = convertCanvasTextOnPathOptions(options);
+    let { maxWidth } = options;
+    if (maxWidth === undefined) {
+      maxWidth = Infinity;
+    } else if (!NumberIsFinite(maxWidth)) {
+      return;
+    }
+    const { side, startOffset } = options;
+    op_canvas_2d_state_fill_text_on_path(
+      OffscreenCanvasRenderingContext2DInternals.getState(this),
+      text,
+      Path2DInternals.getRaw(path),
+      startOffset,
+      textPathSideToRepr[side],
+      maxWidth,
+    );
+  }
+
+  strokeTextOnPath(text, path, options = undefined) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to execute 'strokeTextOnPath' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 2, prefix);
+    text = convertDOMString(text);
+    path = convertPath2D(path);
+    options = convertCanvasTextOnPathOptions(options);
+    let { maxWidth } = options;
+    if (maxWidth === undefined) {
+      maxWidth = Infinity;
+    } else if (!NumberIsFinite(maxWidth)) {
+      return;
+    }
+    const { side, startOffset } = options;
+    op_canvas_2d_state_stroke_text_on_path(
+      OffscreenCanvasRenderingContext2DInternals.getState(this),
+      text,
+      Path2DInternals.getRaw(path),
+      startOffset,
+      textPathSideToRepr[side],
+      maxWidth,
+    );
+  }
+
+  textToPath(text, x, y, maxWidth = undefined) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to execute 'textToPath' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 3, prefix);
+    text = convertDOMString(text);
+    x = convertUnrestrictedDouble(x);
+    y = convertUnrestrictedDouble(y);
+    if (maxWidth === undefined) {
+      maxWidth = Infinity;
+    } else {
+      maxWidth = convertUnrestrictedDouble(maxWidth);
+      if (!NumberIsFinite(maxWidth)) {
+        return createPath2DFromRaw(op_canvas_2d_path_new());
+      }
+    }
+    return createPath2DFromRaw(
+      op_canvas_2d_state_text_to_path(
+        OffscreenCanvasRenderingContext2DInternals.getState(this),
+        text,
+        x,
+        y,
+        maxWidth,
+      ),
+    );
+  }
+
+  layoutText(text, options = undefined) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to execute 'layoutText' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    text = convertDOMString(text);
+    options = convertCanvasTextLayoutOptions(options);
+    const { align, ellipsis, lineHeight = NaN, maxLines = 0, maxWidth } =
+      options;
+    if (!(maxWidth > 0)) {
+      throw new DOMException(
+        "Maximum width must be positive",
+        "IndexSizeError",
+      );
+    }
+    if (
+      options.lineHeight !== undefined &&
+      !(NumberIsFinite(lineHeight) && lineHeight >= 0)
+    ) {
+      throw new DOMException(
+        "Line height must be non-negative and finite",
+        "IndexSizeError",
+      );
+    }
+    if (options.maxLines !== undefined && maxLines === 0) {
+      throw new DOMException(
+        "Maximum number of lines must be positive",
+        "IndexSizeError",
+      );
+    }
+    return createTextLayoutFromRaw(
+      op_canvas_2d_state_layout_text(
+        OffscreenCanvasRenderingContext2DInternals.getState(this),
+        text,
+        maxWidth,
+        lineHeight,
+        textLayoutAlignToRepr[align],
+        maxLines,
+        ellipsis,
+      ),
+    );
+  }
+
+  fillTextLayout(layout, x, y) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to execute 'fillTextLayout' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 3, prefix);
+    layout = convertTextLayout(layout);
+    x = convertUnrestrictedDouble(x);
+    y = convertUnrestrictedDouble(y);
+    op_canvas_2d_state_fill_text_layout(
+      OffscreenCanvasRenderingContext2DInternals.getState(this),
+      TextLayoutInternals.getRaw(layout),
+      x,
+      y,
+    );
+  }
+
+  strokeTextLayout(layout, x, y) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to execute 'strokeTextLayout' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 3, prefix);
+    layout = convertTextLayout(layout);
+    x = convertUnrestrictedDouble(x);
+    y = convertUnrestrictedDouble(y);
+    op_canvas_2d_state_stroke_text_layout(
+      OffscreenCanvasRenderingContext2DInternals.getState(this),
+      TextLayoutInternals.getRaw(layout),
+      x,
+      y,
+    );
+  }
+
   measureText(text) {
     OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
     const prefix =
       "Failed to execute 'measureText' on 'OffscreenCanvasRenderingContext2D'";
     requiredArguments(arguments.length, 1, prefix);
     text = convertDOMString(text);
-    op_canvas_2d_state_measure_text(
+    const raw = op_canvas_2d_state_measure_text(
       OffscreenCanvasRenderingContext2DInternals.getState(this),
       text,
       measureTextBuffer,
     );
-    return createTextMetrics(measureTextBuffer);
+    return createTextMetricsFromRaw(raw, text.length, measureTextBuffer);
   }
 
   drawImage(image, sx, sy, sw = undefined, sh, dx, dy, dw, dh) {
@@ -2697,6 +3688,62 @@ export class OffscreenCanvasRenderingContext2D extends Object {
     OffscreenCanvasRenderingContext2DInternals.invalidateCachedFont(this);
   }
 
+  get fontVariantNumeric() {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    return OffscreenCanvasRenderingContext2DInternals.getFontVariantNumeric(this);
+  }
+
+  set fontVariantNumeric(value) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to set 'fontVariantNumeric' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    value = convertDOMString(value);
+    OffscreenCanvasRenderingContext2DInternals.setFontVariantNumeric(this, value);
+  }
+
+  get fontVariantLigatures() {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    return OffscreenCanvasRenderingContext2DInternals.getFontVariantLigatures(this);
+  }
+
+  set fontVariantLigatures(value) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to set 'fontVariantLigatures' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    value = convertDOMString(value);
+    OffscreenCanvasRenderingContext2DInternals.setFontVariantLigatures(this, value);
+  }
+
+  get fontFeatureSettings() {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    return OffscreenCanvasRenderingContext2DInternals.getFontFeatureSettings(this);
+  }
+
+  set fontFeatureSettings(value) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to set 'fontFeatureSettings' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    value = convertDOMString(value);
+    OffscreenCanvasRenderingContext2DInternals.setFontFeatureSettings(this, value);
+  }
+
+  get fontVariationSettings() {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    return OffscreenCanvasRenderingContext2DInternals.getFontVariationSettings(this);
+  }
+
+  set fontVariationSettings(value) {
+    OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
+    const prefix =
+      "Failed to set 'fontVariationSettings' on 'OffscreenCanvasRenderingContext2D'";
+    requiredArguments(arguments.length, 1, prefix);
+    value = convertDOMString(value);
+    OffscreenCanvasRenderingContext2DInternals.setFontVariationSettings(this, value);
+  }
+
   get textRendering() {
     OffscreenCanvasRenderingContext2DInternals.checkInstance(this);
     return textRenderingFromRepr[
@@ -0,0 +1,448 @@
+use std::mem;
+
+use euclid::default::{Point2D, Transform2D};
+use euclid::{point2, vec2};
+use image::ImageFormat;
+use palette::Srgb;
+use ttf_parser::{GlyphId, RasterImageFormat, RgbaColor, colr};
+
+use super::css::color::{AbsoluteColor, AbsoluteColorValue};
+use super::image_bitmap::{ImageBitmap, ResizeQuality};
+use super::image_data::ImageData;
+use super::path::{CanvasFillRule, Path, PathBooleanOperation};
+use super::{
+    CanvasColorSpace, PredefinedColorSpace, raqote_ext, to_raqote_color, to_raqote_solid_source,
+};
+
+#[derive(Clone, Copy, Debug)]
+struct ColorStop {
+    offset: f32,
+    color: AbsoluteColor,
+}
+
+#[derive(Clone, Debug)]
+enum ColorGlyphPaint {
+    Solid(AbsoluteColor),
+    LinearGradient {
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        stops: Vec<ColorStop>,
+        spread: raqote::Spread,
+    },
+    RadialGradient {
+        start_center: Point2D<f32>,
+        start_radius: f32,
+        end_center: Point2D<f32>,
+        end_radius: f32,
+        stops: Vec<ColorStop>,
+        spread: raqote::Spread,
+    },
+    SweepGradient {
+        center: Point2D<f32>,
+        start_angle: f32,
+        end_angle: f32,
+        stops: Vec<ColorStop>,
+        spread: raqote::Spread,
+    },
+    Image(ImageBitmap),
+}
+
+/// One filled shape of a colour glyph. `path` is in the same space as the
+/// glyph's outline; the paint is defined in its own space, which
+/// `paint_transform` maps into that of `path`.
+#[derive(Clone, Debug)]
+pub struct ColorGlyphLayer {
+    pub path: Path,
+    paint: ColorGlyphPaint,
+    paint_transform: Transform2D<f64>,
+}
+
+impl ColorGlyphLayer {
+    pub fn transform(self, mat: &Transform2D<f64>) -> Self {
+        Self {
+            path: self.path.transform(mat),
+            paint: self.paint,
+            paint_transform: self.paint_transform.then(mat),
+        }
+    }
+
+    pub fn to_raqote(
+        &self,
+        destination_color_space: CanvasColorSpace,
+        image_smoothing_enabled: bool,
+    ) -> Option<raqote_ext::OwnedSource> {
+        fn gradient(
+            stops: &[ColorStop],
+            destination_color_space: CanvasColorSpace,
+        ) -> Option<raqote::Gradient> {
+            if stops.is_empty() {
+                return None;
+            }
+            Some(raqote::Gradient {
+                stops: stops
+                    .iter()
+                    .map(|stop| raqote::GradientStop {
+                        position: stop.offset,
+                        color: to_raqote_color(stop.color, destination_color_space),
+                    })
+                    .collect(),
+            })
+        }
+
+        let mut paint_transform = self.paint_transform;
+        let source = match self.paint {
+            ColorGlyphPaint::Solid(color) => {
+                return Some(raqote_ext::OwnedSource::Solid(to_raqote_solid_source(
+                    color,
+                    destination_color_space,
+                )));
+            }
+            ColorGlyphPaint::LinearGradient {
+                start,
+                end,
+                ref stops,
+                spread,
+            } => {
+                if start == end {
+                    return None;
+                }
+                raqote_ext::OwnedSource::new_linear_gradient(
+                    gradient(stops, destination_color_space)?,
+                    start,
+                    end,
+                    spread,
+                )
+            }
+            ColorGlyphPaint::RadialGradient {
+                start_center,
+                start_radius,
+                end_center,
+                end_radius,
+                ref stops,
+                spread,
+            } => {
+                if start_center == end_center && start_radius == end_radius {
+                    return None;
+                }
+                raqote_ext::OwnedSource::new_two_circle_radial_gradient(
+                    gradient(stops, destination_color_space)?,
+                    start_center,
+                    start_radius,
+                    end_center,
+                    end_radius,
+                    spread,
+                )
+            }
+            ColorGlyphPaint::SweepGradient {
+                center,
+                start_angle,
+                end_angle,
+                ref stops,
+                spread,
+            } => {
+                // Raqote measures sweep angles counterclockwise in a y-down
+                // space, and `COLR` counterclockwise in a y-up one.
+                paint_transform = Transform2D::scale(1.0, -1.0).then(&paint_transform);
+                raqote_ext::OwnedSource::new_sweep_gradient(
+                    gradient(stops, destination_color_space)?,
+                    point2(center.x, -center.y),
+                    start_angle,
+                    end_angle,
+                    spread,
+                )
+            }
+            ColorGlyphPaint::Image(ref image) => raqote_ext::OwnedSource::Image(
+                image
+                    .clone()
+                    .into_raqote_image(destination_color_space)
+                    .ok()??,
+                raqote::ExtendMode::Pad,
+                if image_smoothing_enabled {
+                    raqote::FilterMode::Bilinear
+                } else {
+                    raqote::FilterMode::Nearest
+                },
+                raqote::Transform::identity(),
+                false,
+                false,
+            ),
+        };
+        source.transformed(&paint_transform.cast())
+    }
+}
+
+fn color_from_rgba(color: RgbaColor) -> AbsoluteColor {
+    AbsoluteColor {
+        value: AbsoluteColorValue::LegacyRgb(Srgb::new(color.red, color.green, color.blue)),
+        alpha: color.alpha as f32 / 255.0,
+    }
+}
+
+fn rgba_from_color(color: AbsoluteColor) -> RgbaColor {
+    let rgb = Srgb::<u8>::from_linear(color.value.into_linear_srgb());
+    RgbaColor::new(
+        rgb.red,
+        rgb.green,
+        rgb.blue,
+        (color.alpha.clamp(0.0, 1.0) * 255.0).round() as u8,
+    )
+}
+
+/// The text colour as `COLR` layers see it, for caching the layers of a glyph
+/// by the colour they were painted with.
+pub fn color_glyph_foreground_key(color: AbsoluteColor) -> [u8; 4] {
+    let color = rgba_from_color(color);
+    [color.red, color.green, color.blue, color.alpha]
+}
+
+fn spread_from_extend(extend: colr::GradientExtend) -> raqote::Spread {
+    match extend {
+        colr::GradientExtend::Pad => raqote::Spread::Pad,
+        colr::GradientExtend::Repeat => raqote::Spread::Repeat,
+        colr::GradientExtend::Reflect => raqote::Spread::Reflect,
+    }
+}
+
+struct OutlineBuilder<'a> {
+    path: &'a mut Path,
+    transform: Transform2D<f32>,
+}
+
+impl ttf_parser::OutlineBuilder for OutlineBuilder<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let p = self.transform.transform_point(point2(x, y)).cast();
+        self.path.move_to(p.x, p.y)
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = self.transform.transform_point(point2(x, y)).cast();
+        self.path.line_to(p.x, p.y)
+    }
+
+    fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
+        let c = self.transform.transform_point(point2(cx, cy)).cast();
+        let p = self.transform.transform_point(point2(x, y)).cast();
+        self.path.quad_to(c.x, c.y, p.x, p.y)
+    }
+
+    fn curve_to(&mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) {
+        let c1 = self.transform.transform_point(point2(c1x, c1y)).cast();
+        let c2 = self.transform.transform_point(point2(c2x, c2y)).cast();
+        let p = self.transform.transform_point(point2(x, y)).cast();
+        self.path.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y)
+    }
+
+    fn close(&mut self) {
+        self.path.close()
+    }
+}
+
+/// Flattens the paint graph of a `COLR` glyph into a list of filled layers.
+/// Clips are intersected into the layer's path. Composite modes other than
+/// source-over are not supported, so nested layers are drawn as if they were
+/// source-over.
+struct ColorGlyphPainter<'a, 'f> {
+    face: &'f ttf_parser::Face<'a>,
+    transforms: Vec<Transform2D<f32>>,
+    outline: Path,
+    clips: Vec<Path>,
+    layers: Vec<ColorGlyphLayer>,
+}
+
+impl ColorGlyphPainter<'_, '_> {
+    fn transform(&self) -> Transform2D<f32> {
+        *self.transforms.last().unwrap()
+    }
+
+    fn clip(&self) -> Option<Path> {
+        let (first, rest) = self.clips.split_first()?;
+        Some(rest.iter().fold(first.clone(), |clip, other| {
+            clip.boolean_op(
+                CanvasFillRule::NonZero,
+                other,
+                CanvasFillRule::NonZero,
+                PathBooleanOperation::Intersection,
+            )
+        }))
+    }
+
+    fn stops(stops: impl Iterator<Item = colr::ColorStop>) -> Vec<ColorStop> {
+        stops
+            .map(|stop| ColorStop {
+                offset: stop.stop_offset,
+                color: color_from_rgba(stop.color),
+            })
+            .collect()
+    }
+}
+
+impl<'a> colr::Painter<'a> for ColorGlyphPainter<'a, '_> {
+    fn outline_glyph(&mut self, glyph_id: GlyphId) {
+        self.outline.clear();
+        let transform = self.transform();
+        self.face.outline_glyph(
+            glyph_id,
+            &mut OutlineBuilder {
+                path: &mut self.outline,
+                transform,
+            },
+        );
+    }
+
+    fn paint(&mut self, paint: colr::Paint<'a>) {
+        let Some(path) = self.clip() else {
+            return;
+        };
+        let paint = match paint {
+            colr::Paint::Solid(color) => ColorGlyphPaint::Solid(color_from_rgba(color)),
+            colr::Paint::LinearGradient(gradient) => {
+                // `COLR` orients the gradient perpendicular to p0–p2 instead of
+                // along p0–p1; project p1 onto that direction.
+                let p0 = point2(gradient.x0, gradient.y0);
+                let p1 = point2(gradient.x1, gradient.y1);
+                let p2 = point2(gradient.x2, gradient.y2);
+                let direction = p2 - p0;
+                let normal = vec2(-direction.y, direction.x);
+                let end = if normal.square_length() > 0.0 {
+                    p0 + normal * ((p1 - p0).dot(normal) / normal.square_length())
+                } else {
+                    p1
+                };
+                ColorGlyphPaint::LinearGradient {
+                    start: p0,
+                    end,
+                    stops: Self::stops(gradient.stops(0, &[])),
+                    spread: spread_from_extend(gradient.extend),
+                }
+            }
+            colr::Paint::RadialGradient(gradient) => ColorGlyphPaint::RadialGradient {
+                start_center: point2(gradient.x0, gradient.y0),
+                start_radius: gradient.r0,
+                end_center: point2(gradient.x1, gradient.y1),
+                end_radius: gradient.r1,
+                stops: Self::stops(gradient.stops(0, &[])),
+                spread: spread_from_extend(gradient.extend),
+            },
+            colr::Paint::SweepGradient(gradient) => ColorGlyphPaint::SweepGradient {
+                center: point2(gradient.center_x, gradient.center_y),
+                // Angles are stored in half turns.
+                start_angle: gradient.start_angle * 180.0,
+                end_angle: gradient.end_angle * 180.0,
+                stops: Self::stops(gradient.stops(0, &[])),
+                spread: spread_from_extend(gradient.extend),
+            },
+        };
+        self.layers.push(ColorGlyphLayer {
+            path,
+            paint,
+            paint_transform: self.transform().cast(),
+        });
+    }
+
+    fn push_clip(&mut self) {
+        self.clips.push(mem::take(&mut self.outline));
+    }
+
+    fn push_clip_box(&mut self, clipbox: colr::ClipBox) {
+        let mut path = Path::new();
+        path.rect(
+            clipbox.x_min as f64,
+            clipbox.y_min as f64,
+            (clipbox.x_max - clipbox.x_min) as f64,
+            (clipbox.y_max - clipbox.y_min) as f64,
+        );
+        self.clips.push(path.transform(&self.transform().cast()));
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn push_layer(&mut self, _: colr::CompositeMode) {}
+
+    fn pop_layer(&mut self) {}
+
+    fn push_transform(&mut self, transform: ttf_parser::Transform) {
+        let ttf_parser::Transform { a, b, c, d, e, f } = transform;
+        let transform = Transform2D::new(a, b, c, d, e, f).then(&self.transform());
+        self.transforms.push(transform);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+}
+
+/// Returns the layers of glyph `glyph_id` if it is a colour glyph, using
+/// `COLR`/`CPAL` if present, otherwise the `CBDT` or `sbix` strike closest to
+/// `pixels_per_em`. `transform` maps font units to the space the layers are
+/// returned in, and `foreground` is the colour of `COLR` layers that use the
+/// text colour.
+pub fn color_glyph_layers(
+    face: &ttf_parser::Face<'_>,
+    glyph_id: u32,
+    transform: Transform2D<f32>,
+    pixels_per_em: f32,
+    foreground: AbsoluteColor,
+) -> Vec<ColorGlyphLayer> {
+    let Ok(glyph_id) = u16::try_from(glyph_id).map(GlyphId) else {
+        return vec![];
+    };
+    if face.is_color_glyph(glyph_id) {
+        let mut painter = ColorGlyphPainter {
+            face,
+            transforms: vec![transform],
+            outline: Path::new(),
+            clips: Vec::new(),
+            layers: Vec::new(),
+        };
+        face.paint_color_glyph(glyph_id, 0, rgba_from_color(foreground), &mut painter);
+        return painter.layers;
+    }
+    let pixels_per_em = pixels_per_em.round().clamp(1.0, u16::MAX as f32) as u16;
+    let Some(raster) = face.glyph_raster_image(glyph_id, pixels_per_em) else {
+        return vec![];
+    };
+    if raster.format != RasterImageFormat::PNG || raster.pixels_per_em == 0 {
+        return vec![];
+    }
+    let Ok(image) = image::load_from_memory_with_format(raster.data, ImageFormat::Png) else {
+        return vec![];
+    };
+    let image = image.into_rgba8();
+    let (width, height) = image.dimensions();
+    let image = ImageBitmap::from_image_data_resize(
+        ImageData {
+            width,
+            height,
+            color_space: PredefinedColorSpace::Srgb,
+            data: image.into_vec(),
+        },
+        width,
+        height,
+        ResizeQuality::Low,
+        false,
+    );
+    // The strike's origin is the bottom left corner of the image, in pixels
+    // above the baseline, while the image's rows go down.
+    let scale = face.units_per_em() as f32 / raster.pixels_per_em as f32;
+    let x = raster.x as f32 * scale;
+    let y = raster.y as f32 * scale;
+    let paint_transform = Transform2D::new(
+        raster.width as f32 / width as f32 * scale,
+        0.0,
+        0.0,
+        -(raster.height as f32) / height as f32 * scale,
+        x,
+        y + raster.height as f32 * scale,
+    )
+    .then(&transform)
+    .cast();
+    let mut path = Path::new();
+    path.rect(0.0, 0.0, width as f64, height as f64);
+    vec![ColorGlyphLayer {
+        path: path.transform(&paint_transform),
+        paint: ColorGlyphPaint::Image(image),
+        paint_transform,
+    }]
+}
@@ -1,5 +1,7 @@
 use std::convert::Infallible;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
 use std::rc::Rc;
 
 use cssparser::{
@@ -9,13 +11,101 @@ use cssparser::{
 use super::super::angle::{ComputedAngle, SpecifiedAngle};
 use super::super::{
     CssNumber, CssPercentage, CssString, CssValue, FromCss, parse_integer_with_range, parse_number,
-    parse_url, try_match_next_ident_ignore_ascii_case,
+    parse_string, parse_url, try_match_next_ident_ignore_ascii_case,
 };
 use super::{ComputedSpecificFamily, SpecifiedAbsoluteFontWeight, SpecifiedFontWidth};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecifiedFontFormat {
+    Collection,
+    EmbeddedOpentype,
+    Opentype,
+    Svg,
+    Truetype,
+    Woff,
+    Woff2,
+    /// A string or value that names none of the formats above.
+    Unknown,
+}
+
+impl FromCss for SpecifiedFontFormat {
+    type Err = Infallible;
+
+    fn from_css<'i>(input: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i, Self::Err>> {
+        let location = input.current_source_location();
+        Ok(match *input.next()? {
+            // The `-variations` strings are legacy spellings of the format
+            // combined with `tech(variations)`.
+            Token::QuotedString(ref s) => match_ignore_ascii_case! { s,
+                "collection" => Self::Collection,
+                "embedded-opentype" => Self::EmbeddedOpentype,
+                "opentype" | "opentype-variations" => Self::Opentype,
+                "svg" => Self::Svg,
+                "truetype" | "truetype-variations" => Self::Truetype,
+                "woff" | "woff-variations" => Self::Woff,
+                "woff2" | "woff2-variations" => Self::Woff2,
+                _ => Self::Unknown,
+            },
+            Token::Ident(ref ident) => match_ignore_ascii_case! { ident,
+                "collection" => Self::Collection,
+                "embedded-opentype" => Self::EmbeddedOpentype,
+                "opentype" => Self::Opentype,
+                "svg" => Self::Svg,
+                "truetype" => Self::Truetype,
+                "woff" => Self::Woff,
+                "woff2" => Self::Woff2,
+                _ => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone()))),
+            },
+            ref t => return Err(location.new_unexpected_token_error(t.clone())),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecifiedFontTech {
+    FeaturesOpentype,
+    FeaturesAat,
+    FeaturesGraphite,
+    ColorColrv0,
+    ColorColrv1,
+    ColorSvg,
+    ColorSbix,
+    ColorCbdt,
+    Variations,
+    Palettes,
+    Incremental,
+    /// A keyword or value that names none of the technologies above.
+    Unknown,
+}
+
+impl FromCss for SpecifiedFontTech {
+    type Err = Infallible;
+
+    fn from_css<'i>(input: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i, Self::Err>> {
+        try_match_next_ident_ignore_ascii_case! { input,
+            "features-opentype" => Self::FeaturesOpentype,
+            "features-aat" => Self::FeaturesAat,
+            "features-graphite" => Self::FeaturesGraphite,
+            "color-colrv0" => Self::ColorColrv0,
+            "color-colrv1" => Self::ColorColrv1,
+            "color-svg" => Self::ColorSvg,
+            "color-sbix" => Self::ColorSbix,
+            "color-cbdt" => Self::ColorCbdt,
+            "variations" => Self::Variations,
+            "palettes" => Self::Palettes,
+            "incremental" => Self::Incremental,
+        }
+        .map_err(Into::into)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum SpecifiedFontSource {
-    Url(Rc<str>),
+    Url {
+        url: Rc<str>,
+        format: Option<SpecifiedFontFormat>,
+        tech: Rc<[SpecifiedFontTech]>,
+    },
     Local(#[allow(dead_code)] ComputedSpecificFamily),
 }
 
@@ -29,13 +119,56 @@ impl FromCss for SpecifiedFontSource {
         }) {
             return Ok(Self::Local(v));
         }
+        // Formats and technologies that are not recognised make the source
+        // unsupported instead of invalidating the whole list, so that the
+        // next source is tried.
         let url = parse_url(input)?;
-        // TODO `format(<font-format>)`
-        // TODO `tech(<font-tech>#)`
-        Ok(Self::Url(url))
+        let mut format = None;
+        if input
+            .try_parse(|input| input.expect_function_matching("format"))
+            .is_ok()
+        {
+            format = Some(input.parse_nested_block(|input| {
+                let format = input
+                    .try_parse(SpecifiedFontFormat::from_css)
+                    .ok()
+                    .filter(|_| input.is_exhausted());
+                skip_remaining(input);
+                Ok::<_, ParseError<Self::Err>>(format.unwrap_or(SpecifiedFontFormat::Unknown))
+            })?);
+        }
+        let mut tech = Vec::new();
+        if input
+            .try_parse(|input| input.expect_function_matching("tech"))
+            .is_ok()
+        {
+            tech = input.parse_nested_block(|input| {
+                input.parse_comma_separated(|input| {
+                    let tech = input
+                        .try_parse(SpecifiedFontTech::from_css)
+                        .ok()
+                        .filter(|_| input.is_exhausted());
+                    skip_remaining(input);
+                    Ok::<_, ParseError<Self::Err>>(tech.unwrap_or(SpecifiedFontTech::Unknown))
+                })
+            })?;
+        }
+        if !input.is_exhausted() {
+            skip_remaining(input);
+            format = Some(SpecifiedFontFormat::Unknown);
+        }
+        Ok(Self::Url {
+            url,
+            format,
+            tech: tech.into(),
+        })
     }
 }
 
+fn skip_remaining(input: &mut Parser) {
+    while input.next().is_ok() {}
+}
+
 #[derive(Clone, Debug)]
 pub struct SpecifiedFontSources {
     pub font_source_list: Rc<[SpecifiedFontSource]>,
@@ -268,7 +401,7 @@ fn parse_opentype_tag<'i>(
     Ok(tag.as_bytes().try_into().unwrap())
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct SpecifiedFeatureTagValue {
     pub tag: [u8; 4],
     pub value: u32,
@@ -309,7 +442,7 @@ impl ToCss for SpecifiedFeatureTagValue {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct SpecifiedFontFeatureSettings {
     pub feature_tag_value_list: Option<Rc<[SpecifiedFeatureTagValue]>>,
 }
@@ -356,6 +489,22 @@ pub struct SpecifiedVariationValue {
     pub value: f32,
 }
 
+// Values are compared by their bits so that settings can key caches.
+impl PartialEq for SpecifiedVariationValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.tag == other.tag && self.value.to_bits() == other.value.to_bits()
+    }
+}
+
+impl Eq for SpecifiedVariationValue {}
+
+impl Hash for SpecifiedVariationValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tag.hash(state);
+        self.value.to_bits().hash(state);
+    }
+}
+
 impl FromCss for SpecifiedVariationValue {
     type Err = Infallible;
 
@@ -377,7 +526,7 @@ impl ToCss for SpecifiedVariationValue {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct SpecifiedFontVariationSettings {
     pub variation_value_list: Option<Rc<[SpecifiedVariationValue]>>,
 }
@@ -516,3 +665,142 @@ impl ToCss for SpecifiedMetricsOverride {
         Ok(())
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpecifiedSizeAdjust(pub f32);
+
+impl Default for SpecifiedSizeAdjust {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl FromCss for SpecifiedSizeAdjust {
+    type Err = Infallible;
+
+    fn from_css<'i>(input: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i, Self::Err>> {
+        let location = input.current_source_location();
+        match *input.next()? {
+            Token::Percentage { unit_value, .. } if unit_value >= 0.0 => Ok(Self(unit_value)),
+            ref t => Err(location.new_unexpected_token_error(t.clone())),
+        }
+    }
+}
+
+impl ToCss for SpecifiedSizeAdjust {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        CssPercentage(self.0).to_css(dest)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpecifiedFontSynthesis {
+    pub weight: bool,
+    pub style: bool,
+    pub small_caps: bool,
+    pub position: bool,
+}
+
+impl SpecifiedFontSynthesis {
+    pub const NONE: Self = Self {
+        weight: false,
+        style: false,
+        small_caps: false,
+        position: false,
+    };
+}
+
+impl Default for SpecifiedFontSynthesis {
+    fn default() -> Self {
+        Self {
+            weight: true,
+            style: true,
+            small_caps: true,
+            position: true,
+        }
+    }
+}
+
+impl FromCss for SpecifiedFontSynthesis {
+    type Err = Infallible;
+
+    fn from_css<'i>(input: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i, Self::Err>> {
+        if input
+            .try_parse(|input| input.expect_ident_matching("none"))
+            .is_ok()
+        {
+            return Ok(Self::NONE);
+        }
+        let mut result = Self::NONE;
+        let mut location = input.current_source_location();
+        while let Ok(ref ident) = input.try_parse(Parser::expect_ident_cloned) {
+            let flag = match_ignore_ascii_case! { ident,
+                "weight" => &mut result.weight,
+                "style" => &mut result.style,
+                "small-caps" => &mut result.small_caps,
+                "position" => &mut result.position,
+                _ => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone()))),
+            };
+            if mem::replace(flag, true) {
+                return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())));
+            }
+            location = input.current_source_location();
+        }
+        if result == Self::NONE {
+            return Err(input.new_error_for_next_token());
+        }
+        Ok(result)
+    }
+}
+
+impl ToCss for SpecifiedFontSynthesis {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        let keywords = [
+            (self.weight, "weight"),
+            (self.style, "style"),
+            (self.small_caps, "small-caps"),
+            (self.position, "position"),
+        ];
+        let mut iter = keywords
+            .into_iter()
+            .filter_map(|(enabled, keyword)| enabled.then_some(keyword));
+        let Some(first) = iter.next() else {
+            return dest.write_str("none");
+        };
+        dest.write_str(first)?;
+        for keyword in iter {
+            write!(dest, " {keyword}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SpecifiedFontNamedInstance {
+    #[default]
+    Auto,
+    Name(Rc<str>),
+}
+
+impl FromCss for SpecifiedFontNamedInstance {
+    type Err = Infallible;
+
+    fn from_css<'i>(input: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i, Self::Err>> {
+        if input
+            .try_parse(|input| input.expect_ident_matching("auto"))
+            .is_ok()
+        {
+            return Ok(Self::Auto);
+        }
+        Ok(Self::Name(parse_string(input)?))
+    }
+}
+
+impl ToCss for SpecifiedFontNamedInstance {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            Self::Auto => dest.write_str("auto"),
+            Self::Name(ref name) => serialize_string(name, dest),
+        }
+    }
+}
@@ -2,6 +2,7 @@ pub mod font_face;
 
 use std::convert::Infallible;
 use std::fmt;
+use std::mem;
 use std::rc::Rc;
 
 use cssparser::{ParseError, Parser, ToCss, Token, match_ignore_ascii_case, serialize_string};
@@ -57,7 +58,7 @@ impl ToCss for ComputedFontStyle {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ComputedFontVariantCaps {
     Normal,
     SmallCaps,
@@ -114,6 +115,240 @@ impl ToCss for ComputedFontVariantCss2 {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NumericFigureValue {
+    LiningNums,
+    OldstyleNums,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NumericSpacingValue {
+    ProportionalNums,
+    TabularNums,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NumericFractionValue {
+    DiagonalFractions,
+    StackedFractions,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ComputedFontVariantNumeric {
+    pub figure: Option<NumericFigureValue>,
+    pub spacing: Option<NumericSpacingValue>,
+    pub fraction: Option<NumericFractionValue>,
+    pub ordinal: bool,
+    pub slashed_zero: bool,
+}
+
+impl ComputedFontVariantNumeric {
+    /// The OpenType features that this value turns on.
+    pub fn features(self) -> impl Iterator<Item = [u8; 4]> {
+        [
+            self.figure.map(|v| match v {
+                NumericFigureValue::LiningNums => *b"lnum",
+                NumericFigureValue::OldstyleNums => *b"onum",
+            }),
+            self.spacing.map(|v| match v {
+                NumericSpacingValue::ProportionalNums => *b"pnum",
+                NumericSpacingValue::TabularNums => *b"tnum",
+            }),
+            self.fraction.map(|v| match v {
+                NumericFractionValue::DiagonalFractions => *b"frac",
+                NumericFractionValue::StackedFractions => *b"afrc",
+            }),
+            self.ordinal.then_some(*b"ordn"),
+            self.slashed_zero.then_some(*b"zero"),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl FromCss for ComputedFontVariantNumeric {
+    type Err = Infallible;
+
+    fn from_css<'i>(input: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i, Self::Err>> {
+        let mut result = Self::default();
+        if input
+            .try_parse(|input| input.expect_ident_matching("normal"))
+            .is_ok()
+        {
+            return Ok(result);
+        }
+        let mut location = input.current_source_location();
+        while let Ok(ref ident) = input.try_parse(Parser::expect_ident_cloned) {
+            let duplicate = match_ignore_ascii_case! { ident,
+                "lining-nums" => result.figure.replace(NumericFigureValue::LiningNums).is_some(),
+                "oldstyle-nums" => result.figure.replace(NumericFigureValue::OldstyleNums).is_some(),
+                "proportional-nums" => {
+                    result.spacing.replace(NumericSpacingValue::ProportionalNums).is_some()
+                },
+                "tabular-nums" => result.spacing.replace(NumericSpacingValue::TabularNums).is_some(),
+                "diagonal-fractions" => {
+                    result.fraction.replace(NumericFractionValue::DiagonalFractions).is_some()
+                },
+                "stacked-fractions" => {
+                    result.fraction.replace(NumericFractionValue::StackedFractions).is_some()
+                },
+                "ordinal" => mem::replace(&mut result.ordinal, true),
+                "slashed-zero" => mem::replace(&mut result.slashed_zero, true),
+                _ => true,
+            };
+            if duplicate {
+                return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())));
+            }
+            location = input.current_source_location();
+        }
+        if result == Self::default() {
+            return Err(input.new_error_for_next_token());
+        }
+        Ok(result)
+    }
+}
+
+impl ToCss for ComputedFontVariantNumeric {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        let keywords = [
+            self.figure.map(|v| match v {
+                NumericFigureValue::LiningNums => "lining-nums",
+                NumericFigureValue::OldstyleNums => "oldstyle-nums",
+            }),
+            self.spacing.map(|v| match v {
+                NumericSpacingValue::ProportionalNums => "proportional-nums",
+                NumericSpacingValue::TabularNums => "tabular-nums",
+            }),
+            self.fraction.map(|v| match v {
+                NumericFractionValue::DiagonalFractions => "diagonal-fractions",
+                NumericFractionValue::StackedFractions => "stacked-fractions",
+            }),
+            self.ordinal.then_some("ordinal"),
+            self.slashed_zero.then_some("slashed-zero"),
+        ];
+        write_keyword_list(keywords.into_iter().flatten(), "normal", dest)
+    }
+}
+
+/// Each category is `None` when left to the font, or whether its ligatures are
+/// turned on. `none` is kept as every category turned off.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ComputedFontVariantLigatures {
+    pub common: Option<bool>,
+    pub discretionary: Option<bool>,
+    pub historical: Option<bool>,
+    pub contextual: Option<bool>,
+}
+
+impl ComputedFontVariantLigatures {
+    pub const NONE: Self = Self {
+        common: Some(false),
+        discretionary: Some(false),
+        historical: Some(false),
+        contextual: Some(false),
+    };
+
+    /// The OpenType features that this value turns on or off.
+    pub fn features(self) -> impl Iterator<Item = ([u8; 4], bool)> {
+        [
+            (*b"liga", self.common),
+            (*b"clig", self.common),
+            (*b"dlig", self.discretionary),
+            (*b"hlig", self.historical),
+            (*b"calt", self.contextual),
+        ]
+        .into_iter()
+        .filter_map(|(tag, enabled)| Some((tag, enabled?)))
+    }
+}
+
+impl FromCss for ComputedFontVariantLigatures {
+    type Err = Infallible;
+
+    fn from_css<'i>(input: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i, Self::Err>> {
+        let mut result = Self::default();
+        if let Ok(v) = input.try_parse(|input| {
+            try_match_next_ident_ignore_ascii_case! { input,
+                "normal" => Self::default(),
+                "none" => Self::NONE,
+            }
+        }) {
+            return Ok(v);
+        }
+        let mut location = input.current_source_location();
+        while let Ok(ref ident) = input.try_parse(Parser::expect_ident_cloned) {
+            let duplicate = match_ignore_ascii_case! { ident,
+                "common-ligatures" => result.common.replace(true).is_some(),
+                "no-common-ligatures" => result.common.replace(false).is_some(),
+                "discretionary-ligatures" => result.discretionary.replace(true).is_some(),
+                "no-discretionary-ligatures" => result.discretionary.replace(false).is_some(),
+                "historical-ligatures" => result.historical.replace(true).is_some(),
+                "no-historical-ligatures" => result.historical.replace(false).is_some(),
+                "contextual" => result.contextual.replace(true).is_some(),
+                "no-contextual" => result.contextual.replace(false).is_some(),
+                _ => true,
+            };
+            if duplicate {
+                return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())));
+            }
+            location = input.current_source_location();
+        }
+        if result == Self::default() {
+            return Err(input.new_error_for_next_token());
+        }
+        Ok(result)
+    }
+}
+
+impl ToCss for ComputedFontVariantLigatures {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        if *self == Self::NONE {
+            return dest.write_str("none");
+        }
+        let keywords = [
+            self.common.map(|v| {
+                if v {
+                    "common-ligatures"
+                } else {
+                    "no-common-ligatures"
+                }
+            }),
+            self.discretionary.map(|v| {
+                if v {
+                    "discretionary-ligatures"
+                } else {
+                    "no-discretionary-ligatures"
+                }
+            }),
+            self.historical.map(|v| {
+                if v {
+                    "historical-ligatures"
+                } else {
+                    "no-historical-ligatures"
+                }
+            }),
+            self.contextual
+                .map(|v| if v { "contextual" } else { "no-contextual" }),
+        ];
+        write_keyword_list(keywords.into_iter().flatten(), "normal", dest)
+    }
+}
+
+fn write_keyword_list<'a, W: fmt::Write>(
+    mut keywords: impl Iterator<Item = &'a str>,
+    empty: &str,
+    dest: &mut W,
+) -> fmt::Result {
+    let Some(first) = keywords.next() else {
+        return dest.write_str(empty);
+    };
+    dest.write_str(first)?;
+    for keyword in keywords {
+        write!(dest, " {keyword}")?;
+    }
+    Ok(())
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum SpecifiedAbsoluteFontWeight {
     #[default]
@@ -475,7 +710,23 @@ impl ToCss for ComputedSpecificFamily {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, Default)]
+pub struct ComputedSpecificFamilyList(pub Vec<ComputedSpecificFamily>);
+
+impl FromCss for ComputedSpecificFamilyList {
+    type Err = Infallible;
+
+    fn from_css<'i>(input: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i, Self::Err>> {
+        if input.is_exhausted() {
+            return Ok(Self::default());
+        }
+        Ok(Self(
+            input.parse_comma_separated(ComputedSpecificFamily::from_css)?,
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ComputedGenericFamily {
     Serif,
     SansSerif,
@@ -483,6 +734,7 @@ pub enum ComputedGenericFamily {
     Fantasy,
     Monospace,
     SystemUi,
+    Emoji,
     Math,
     Fangsong,
     Kai,
@@ -506,6 +758,7 @@ impl FromCss for ComputedGenericFamily {
                 "fantasy" => Self::Fantasy,
                 "monospace" => Self::Monospace,
                 "system-ui" => Self::SystemUi,
+                "emoji" => Self::Emoji,
                 "math" => Self::Math,
                 "ui-serif" => Self::UiSerif,
                 "ui-sans-serif" => Self::UiSansSerif,
@@ -540,6 +793,7 @@ impl ToCss for ComputedGenericFamily {
             Self::Fantasy => dest.write_str("fantasy"),
             Self::Monospace => dest.write_str("monospace"),
             Self::SystemUi => dest.write_str("system-ui"),
+            Self::Emoji => dest.write_str("emoji"),
             Self::Math => dest.write_str("math"),
             Self::Fangsong => dest.write_str("generic(fangsong)"),
             Self::Kai => dest.write_str("generic(kai)"),
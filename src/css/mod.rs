@@ -332,6 +332,8 @@ pub enum ValueKind {
     TransformList,
     FontSource,
     FontFamilyName,
+    FontFamilyNameList,
+    GenericFamilyName,
     FontStyleRange,
     FontWeightRange,
     FontWidthRange,
@@ -342,6 +344,9 @@ pub enum ValueKind {
     AscentOverride,
     DescentOverride,
     LineGapOverride,
+    SizeAdjust,
+    FontSynthesis,
+    FontNamedInstance,
     Font,
 }
 
@@ -352,6 +357,8 @@ impl Display for ValueKind {
             ValueKind::TransformList => f.write_str("transform list"),
             ValueKind::FontSource => f.write_str("font source"),
             ValueKind::FontFamilyName => f.write_str("font family name"),
+            ValueKind::FontFamilyNameList => f.write_str("font family name list"),
+            ValueKind::GenericFamilyName => f.write_str("generic family name"),
             ValueKind::FontStyleRange => f.write_str("font style range"),
             ValueKind::FontWeightRange => f.write_str("font weight range"),
             ValueKind::FontWidthRange => f.write_str("font width range"),
@@ -362,6 +369,9 @@ impl Display for ValueKind {
             ValueKind::AscentOverride => f.write_str("ascent override"),
             ValueKind::DescentOverride => f.write_str("descent override"),
             ValueKind::LineGapOverride => f.write_str("line gap override"),
+            ValueKind::SizeAdjust => f.write_str("size adjust"),
+            ValueKind::FontSynthesis => f.write_str("font synthesis"),
+            ValueKind::FontNamedInstance => f.write_str("font named instance"),
             ValueKind::Font => f.write_str("font"),
         }
     }
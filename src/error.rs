@@ -33,15 +33,21 @@ pub enum Canvas2DError {
         kind: css::ValueKind,
         details: css::SyntaxError,
     },
-    #[error("Local font fallback is not supported")]
-    #[class("DOMExceptionSyntaxError")]
-    UnsupportedLocalFontFallback,
+    #[error("No matching local font")]
+    #[class("DOMExceptionNetworkError")]
+    LocalFontNotFound,
+    #[error("No supported font source")]
+    #[class("DOMExceptionNetworkError")]
+    UnsupportedFontSource,
     #[error("Invalid font data")]
     #[class("DOMExceptionSyntaxError")]
     DecodeFont,
     #[error("Invalid font data")]
     #[class("DOMExceptionNetworkError")]
     DecodeFontFromUrl,
+    #[error("Invalid path data")]
+    #[class("DOMExceptionInvalidStateError")]
+    DecodePath,
     #[error("Unsupported image format '{mime_type}'")]
     #[class("DOMExceptionInvalidStateError")]
     UnsupportedImageFormat { mime_type: String },
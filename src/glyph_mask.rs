@@ -0,0 +1,193 @@
+use std::rc::Rc;
+
+use euclid::default::{Box2D, Point2D, Size2D, Transform2D};
+use euclid::{point2, size2, vec2};
+use hashlink::LruCache;
+
+use super::path::{CanvasFillRule, Path};
+use super::text::{GlyphMaskKey, PositionedGlyph, lru_get_or_insert_with};
+
+/// Text is drawn from coverage masks up to this font size in device pixels.
+/// Larger glyphs are filled as paths, which costs little in comparison to
+/// their area and keeps large masks out of the cache.
+pub const MAX_FONT_SIZE: f32 = 64.0;
+
+/// Number of horizontal subpixel positions that glyphs are rasterized at.
+/// Vertical positions are snapped to whole pixels.
+const SUBPIXEL_POSITIONS: u8 = 4;
+
+/// Exponent applied to coverage, which makes up for blending in gamma-encoded
+/// space thinning out antialiased stems.
+const COVERAGE_GAMMA: f32 = 1.0 / 1.2;
+
+#[derive(Debug)]
+struct GlyphMask {
+    origin: Point2D<i32>,
+    size: Size2D<i32>,
+    data: Box<[u8]>,
+}
+
+impl GlyphMask {
+    fn rasterize(path: &Path, subpixel: u8) -> Option<Self> {
+        let x = f64::from(subpixel) / f64::from(SUBPIXEL_POSITIONS);
+        let path = path
+            .clone()
+            .transform(&Transform2D::new(1.0, 0.0, 0.0, -1.0, x, 0.0));
+        let bounds = path.control_bounds()?.round_out().to_i32();
+        if bounds.is_empty() {
+            return None;
+        }
+        let size = bounds.size();
+        let mut draw_target = raqote::DrawTarget::new(size.width, size.height);
+        draw_target.set_transform(&Transform2D::translation(
+            -bounds.min.x as f32,
+            -bounds.min.y as f32,
+        ));
+        draw_target.fill(
+            &path.to_raqote(CanvasFillRule::NonZero),
+            &raqote::Source::Solid(raqote::SolidSource {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            }),
+            &raqote::DrawOptions::new(),
+        );
+        let data = draw_target
+            .get_data()
+            .iter()
+            .map(|&pixel| {
+                let coverage = (pixel >> 24) as f32 * (1.0 / 255.0);
+                (coverage.powf(COVERAGE_GAMMA) * 255.0).round() as u8
+            })
+            .collect();
+        Some(Self {
+            origin: bounds.min,
+            size,
+            data,
+        })
+    }
+}
+
+/// Coverage masks of glyphs, rasterized once per font, size and subpixel
+/// position, so that small text does not have to be filled outline by outline.
+#[derive(Debug)]
+pub struct GlyphMaskCache {
+    masks: LruCache<(GlyphMaskKey, u8), Option<Rc<GlyphMask>>>,
+}
+
+impl GlyphMaskCache {
+    pub fn new() -> Self {
+        Self {
+            masks: LruCache::new(4096),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.masks.clear();
+    }
+
+    /// Draws `glyphs` with their anchor at `origin`, which is in device space.
+    /// The transform of `draw_target` must be a translation, so that the
+    /// masks line up with the pixel grid.
+    pub fn draw(
+        &mut self,
+        draw_target: &mut raqote::DrawTarget,
+        glyphs: &[PositionedGlyph],
+        origin: Point2D<f64>,
+        source: &raqote::Source,
+        options: &raqote::DrawOptions,
+    ) {
+        let positions = f64::from(SUBPIXEL_POSITIONS);
+        let target_bounds = Box2D::from_size(size2(draw_target.width(), draw_target.height()));
+        let mut bounds = Box2D::zero();
+        let mut placed = Vec::with_capacity(glyphs.len());
+        for glyph in glyphs {
+            let x = ((origin.x + f64::from(glyph.origin.x)) * positions).round();
+            let y = (origin.y + f64::from(glyph.origin.y)).round();
+            let pixel_x = (x / positions).floor();
+            let subpixel = (x - pixel_x * positions) as u8;
+            let key = (glyph.key.clone(), subpixel);
+            let mask = lru_get_or_insert_with(&mut self.masks, key, || {
+                GlyphMask::rasterize(&glyph.path, subpixel).map(Rc::new)
+            });
+            let Some(ref mask) = *mask else {
+                continue;
+            };
+            // Glyphs outside the target are dropped before their position is
+            // converted to integers, where far away ones would overflow.
+            let device_min = point2(pixel_x, y) + mask.origin.to_vector().cast();
+            let device_max = device_min + mask.size.to_vector().cast();
+            if !(device_min.x < f64::from(target_bounds.max.x)
+                && device_min.y < f64::from(target_bounds.max.y)
+                && device_max.x > 0.0
+                && device_max.y > 0.0)
+            {
+                continue;
+            }
+            let min = mask.origin + vec2(pixel_x as i32, y as i32);
+            let rect = Box2D::from_origin_and_size(min, mask.size);
+            let Some(rect) = rect.intersection(&target_bounds) else {
+                continue;
+            };
+            bounds = if bounds.is_empty() {
+                rect
+            } else {
+                bounds.union(&rect)
+            };
+            placed.push((min, mask.clone()));
+        }
+        if bounds.is_empty() {
+            return;
+        }
+        let size = bounds.size();
+        let mut data = vec![0u8; (size.width * size.height) as usize];
+        for (min, mask) in placed {
+            let rect = Box2D::from_origin_and_size(min, mask.size).intersection_unchecked(&bounds);
+            let width = rect.width() as usize;
+            for y in rect.min.y..rect.max.y {
+                let src = ((y - min.y) * mask.size.width + rect.min.x - min.x) as usize;
+                let dst = ((y - bounds.min.y) * size.width + rect.min.x - bounds.min.x) as usize;
+                for (pixel, &coverage) in data[dst..][..width].iter_mut().zip(&mask.data[src..]) {
+                    *pixel = pixel.saturating_add(coverage);
+                }
+            }
+        }
+        let mask = raqote::Mask {
+            width: size.width,
+            height: size.height,
+            data,
+        };
+        // `DrawTarget::mask` always composites with source-over at full
+        // opacity, so anything else goes through a layer.
+        if matches!(options.blend_mode, raqote::BlendMode::SrcOver) && options.alpha == 1.0 {
+            draw_target.mask(source, bounds.min.x, bounds.min.y, &mask);
+        } else {
+            draw_target.push_layer_with_blend(options.alpha, options.blend_mode);
+            draw_target.mask(source, bounds.min.x, bounds.min.y, &mask);
+            draw_target.pop_layer();
+        }
+    }
+}
+
+/// Whether `transform` only translates, which is when glyphs can be drawn from
+/// masks.
+pub fn is_translation(transform: &Transform2D<f32>) -> bool {
+    transform.m11 == 1.0 && transform.m12 == 0.0 && transform.m21 == 0.0 && transform.m22 == 1.0
+}
+
+/// The outlines of `glyphs` with their anchor at `origin`, for drawing them
+/// where the transform does not allow masks.
+pub fn glyphs_to_path(glyphs: &[PositionedGlyph], origin: Point2D<f64>) -> Path {
+    let mut path = Path::new();
+    for glyph in glyphs {
+        let origin = origin + glyph.origin.to_vector().cast();
+        path.extend(
+            glyph
+                .path
+                .clone()
+                .transform(&Transform2D::new(1.0, 0.0, 0.0, -1.0, origin.x, origin.y)),
+        );
+    }
+    path
+}
@@ -1,8 +1,10 @@
 mod codec;
+mod color_glyph;
 pub mod convert;
 pub mod css;
 pub mod error;
 mod filter;
+mod glyph_mask;
 mod gradient;
 mod image_bitmap;
 mod image_data;
@@ -10,6 +12,7 @@ mod path;
 mod pattern;
 mod raqote_ext;
 mod state;
+mod system_font;
 mod text;
 mod wrap;
 
@@ -21,6 +24,7 @@ use euclid::{point2, size2};
 pub use image_bitmap::{ImageBitmap, ImageBitmapData};
 use palette::stimulus::IntoStimulus as _;
 use strum_macros::FromRepr;
+pub use system_font::{GenericFontFamilies, SystemFontOptions};
 pub use wrap::Wrap;
 
 #[derive(Clone, Copy, Debug)]
@@ -165,6 +169,14 @@ deno_core::extension!(
         state::op_canvas_2d_state_set_font_stretch,
         state::op_canvas_2d_state_font_variant_caps,
         state::op_canvas_2d_state_set_font_variant_caps,
+        state::op_canvas_2d_state_font_variant_numeric,
+        state::op_canvas_2d_state_set_font_variant_numeric,
+        state::op_canvas_2d_state_font_variant_ligatures,
+        state::op_canvas_2d_state_set_font_variant_ligatures,
+        state::op_canvas_2d_state_font_feature_settings,
+        state::op_canvas_2d_state_set_font_feature_settings,
+        state::op_canvas_2d_state_font_variation_settings,
+        state::op_canvas_2d_state_set_font_variation_settings,
         state::op_canvas_2d_state_text_rendering,
         state::op_canvas_2d_state_set_text_rendering,
         state::op_canvas_2d_state_scale,
@@ -172,6 +184,7 @@ deno_core::extension!(
         state::op_canvas_2d_state_translate,
         state::op_canvas_2d_state_transform,
         state::op_canvas_2d_state_get_transform,
+        state::op_canvas_2d_state_get_inverse_transform,
         state::op_canvas_2d_state_set_transform,
         state::op_canvas_2d_state_reset_transform,
         state::op_canvas_2d_state_fill_style,
@@ -187,10 +200,20 @@ deno_core::extension!(
         state::op_canvas_2d_state_stroke_rect,
         state::op_canvas_2d_state_fill_text,
         state::op_canvas_2d_state_stroke_text,
+        state::op_canvas_2d_state_fill_text_on_path,
+        state::op_canvas_2d_state_stroke_text_on_path,
+        state::op_canvas_2d_state_text_to_path,
+        state::op_canvas_2d_state_layout_text,
+        state::op_canvas_2d_state_fill_text_layout,
+        state::op_canvas_2d_state_stroke_text_layout,
         state::op_canvas_2d_state_measure_text,
         state::op_canvas_2d_state_fill,
         state::op_canvas_2d_state_stroke,
+        state::op_canvas_2d_state_stroke_to_path,
         state::op_canvas_2d_state_clip,
+        state::op_canvas_2d_state_get_device_clip_bounds,
+        state::op_canvas_2d_state_get_local_clip_bounds,
+        state::op_canvas_2d_state_is_rect_clipped_out,
         state::op_canvas_2d_state_is_point_in_path,
         state::op_canvas_2d_state_is_point_in_stroke,
         state::op_canvas_2d_state_draw_image,
@@ -221,6 +244,7 @@ deno_core::extension!(
         pattern::op_canvas_2d_pattern_set_transform,
         path::op_canvas_2d_path_new,
         path::op_canvas_2d_path_from_svg,
+        path::op_canvas_2d_path_from_bytes,
         path::op_canvas_2d_path_clone,
         path::op_canvas_2d_path_clear,
         path::op_canvas_2d_path_extend,
@@ -233,6 +257,18 @@ deno_core::extension!(
         path::op_canvas_2d_path_ellipse,
         path::op_canvas_2d_path_rect,
         path::op_canvas_2d_path_round_rect,
+        path::op_canvas_2d_path_get_tight_bounds,
+        path::op_canvas_2d_path_get_control_bounds,
+        path::op_canvas_2d_path_get_length,
+        path::op_canvas_2d_path_get_point_at_length,
+        path::op_canvas_2d_path_get_subpath_count,
+        path::op_canvas_2d_path_to_svg,
+        path::op_canvas_2d_path_to_bytes,
+        path::op_canvas_2d_path_dash,
+        path::op_canvas_2d_path_flatten,
+        path::op_canvas_2d_path_simplify,
+        path::op_canvas_2d_path_offset,
+        path::op_canvas_2d_path_boolean_op,
         path::op_canvas_2d_path_close,
         image_bitmap::op_canvas_2d_image_bitmap_from_canvas_state,
         image_bitmap::op_canvas_2d_image_bitmap_from_canvas_state_crop,
@@ -274,12 +310,31 @@ deno_core::extension!(
         text::op_canvas_2d_font_face_set_descent_override,
         text::op_canvas_2d_font_face_line_gap_override,
         text::op_canvas_2d_font_face_set_line_gap_override,
+        text::op_canvas_2d_font_face_size_adjust,
+        text::op_canvas_2d_font_face_set_size_adjust,
+        text::op_canvas_2d_font_face_synthesis,
+        text::op_canvas_2d_font_face_set_synthesis,
+        text::op_canvas_2d_font_face_named_instance,
+        text::op_canvas_2d_font_face_set_named_instance,
+        text::op_canvas_2d_font_face_variation_axes,
+        text::op_canvas_2d_font_face_named_instances,
         text::op_canvas_2d_font_face_load,
+        text::op_canvas_2d_font_face_load_local,
         text::op_canvas_2d_font_face_set_insert,
         text::op_canvas_2d_font_face_set_remove,
         text::op_canvas_2d_font_face_set_clear,
+        text::op_canvas_2d_font_face_set_set_generic_family,
         text::op_canvas_2d_font_face_set_match,
         text::op_canvas_2d_font_source,
+        text::op_canvas_2d_text_layout_get_metrics,
+        text::op_canvas_2d_text_layout_get_line_count,
+        text::op_canvas_2d_text_layout_get_line,
+        text::op_canvas_2d_text_layout_to_path,
+        text::op_canvas_2d_text_metrics_get_glyph_count,
+        text::op_canvas_2d_text_metrics_get_glyph,
+        text::op_canvas_2d_text_metrics_get_selection_rects,
+        text::op_canvas_2d_text_metrics_get_actual_bounding_box,
+        text::op_canvas_2d_text_metrics_get_index_from_offset,
     ],
     esm = [
         dir "js",
@@ -312,6 +367,7 @@ deno_core::extension!(
         "04_create_sequence_from_iterable.js",
         "05_convert_array_buffer.js",
         "05_convert_boolean.js",
+        "05_convert_buffer_source.js",
         "05_convert_data_view.js",
         "05_convert_dom_string.js",
         "05_convert_double.js",
@@ -325,6 +381,7 @@ deno_core::extension!(
         "05_convert_predefined_color_space.js",
         "05_convert_typed_array.js",
         "05_convert_unrestricted_double.js",
+        "05_convert_unsigned_long.js",
         "15_event.js",
         "15_geometry.js",
         "16_canvas.js",
@@ -332,7 +389,15 @@ deno_core::extension!(
         "17_context_2d.js",
         "17_context_bitmaprenderer.js",
     ],
-    state = |state| {
-        text::init(state);
+    options = {
+        system_font_options: SystemFontOptions,
+        generic_font_families: GenericFontFamilies,
+    },
+    state = |state, options| {
+        text::init(
+            state,
+            options.system_font_options,
+            options.generic_font_families,
+        );
     },
 );
@@ -1,13 +1,19 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::f64::consts::TAU;
 use std::ffi::CStr;
+use std::ops::Range;
 
 use deno_core::{GarbageCollected, op2, v8};
-use euclid::default::{Box2D, Point2D, Transform2D};
+use euclid::default::{Box2D, Point2D, Transform2D, Vector2D};
 use euclid::{Angle, point2, size2, vec2};
-use lyon_geom::{Arc, ArcFlags, SvgArc};
+use lyon_geom::{
+    Arc, ArcFlags, CubicBezierSegment, LineSegment, QuadraticBezierSegment, Segment as _, SvgArc,
+};
 use strum_macros::FromRepr;
 
+use super::error::Canvas2DError;
+use super::state::CanvasLineJoin;
 use super::wrap::Wrap;
 
 #[derive(Clone, Copy, Debug, FromRepr)]
@@ -24,6 +30,33 @@ impl CanvasFillRule {
             Self::EvenOdd => raqote::Winding::EvenOdd,
         }
     }
+
+    fn contains(self, winding: i32) -> bool {
+        match self {
+            Self::NonZero => winding != 0,
+            Self::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, FromRepr)]
+#[repr(i32)]
+pub enum PathBooleanOperation {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+impl PathBooleanOperation {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Self::Union => a || b,
+            Self::Intersection => a && b,
+            Self::Difference => a && !b,
+            Self::Xor => a != b,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -69,6 +102,478 @@ impl PathOp {
     }
 }
 
+const CURVE_TOLERANCE: f64 = 0.01;
+const MIN_TOLERANCE: f64 = 1e-6;
+
+#[derive(Clone, Copy, Debug)]
+enum Curve {
+    Line(LineSegment<f64>),
+    Quad(QuadraticBezierSegment<f64>),
+    Cubic(CubicBezierSegment<f64>),
+}
+
+impl Curve {
+    fn from(&self) -> Point2D<f64> {
+        match self {
+            Self::Line(s) => s.from,
+            Self::Quad(s) => s.from,
+            Self::Cubic(s) => s.from,
+        }
+    }
+
+    fn to(&self) -> Point2D<f64> {
+        match self {
+            Self::Line(s) => s.to,
+            Self::Quad(s) => s.to,
+            Self::Cubic(s) => s.to,
+        }
+    }
+
+    fn sample(&self, t: f64) -> Point2D<f64> {
+        match self {
+            Self::Line(s) => s.sample(t),
+            Self::Quad(s) => s.sample(t),
+            Self::Cubic(s) => s.sample(t),
+        }
+    }
+
+    fn derivative(&self, t: f64) -> Vector2D<f64> {
+        match self {
+            Self::Line(s) => s.to_vector(),
+            Self::Quad(s) => s.derivative(t),
+            Self::Cubic(s) => s.derivative(t),
+        }
+    }
+
+    fn bounding_box(&self) -> Box2D<f64> {
+        match self {
+            Self::Line(s) => s.bounding_box(),
+            Self::Quad(s) => s.bounding_box(),
+            Self::Cubic(s) => s.bounding_box(),
+        }
+    }
+
+    fn length(&self) -> f64 {
+        match self {
+            Self::Line(s) => s.length(),
+            Self::Quad(s) => s.length(),
+            Self::Cubic(s) => s.approximate_length(CURVE_TOLERANCE),
+        }
+    }
+
+    fn for_each_flattened_with_t(
+        &self,
+        tolerance: f64,
+        callback: &mut impl FnMut(&LineSegment<f64>, Range<f64>),
+    ) {
+        match self {
+            Self::Line(s) => callback(s, 0.0..1.0),
+            Self::Quad(s) => s.for_each_flattened_with_t(tolerance, callback),
+            Self::Cubic(s) => s.for_each_flattened_with_t(tolerance, callback),
+        }
+    }
+
+    fn t_at_length(&self, distance: f64) -> f64 {
+        if let Self::Line(s) = self {
+            let length = s.length();
+            return if length > 0.0 { distance / length } else { 0.0 };
+        }
+        let mut remaining = distance;
+        let mut result = None;
+        self.for_each_flattened_with_t(CURVE_TOLERANCE, &mut |line, t| {
+            if result.is_some() {
+                return;
+            }
+            let length = line.length();
+            if remaining <= length {
+                result = Some(if length > 0.0 {
+                    t.start + (t.end - t.start) * remaining / length
+                } else {
+                    t.start
+                });
+            } else {
+                remaining -= length;
+            }
+        });
+        result.unwrap_or(1.0)
+    }
+
+    /// Returns the part of the curve between `t0` and `t1` (in either order),
+    /// with its end points moved to `from` and `to`.
+    fn fit(&self, t0: f64, t1: f64, from: Point2D<f64>, to: Point2D<f64>) -> Self {
+        let range = t0.min(t1)..t0.max(t1);
+        match self {
+            Self::Line(_) => Self::Line(LineSegment { from, to }),
+            Self::Quad(s) => {
+                let s = s.split_range(range);
+                let s = if t0 > t1 { s.flip() } else { s };
+                Self::Quad(QuadraticBezierSegment { from, to, ..s })
+            }
+            Self::Cubic(s) => {
+                let s = s.split_range(range);
+                let s = if t0 > t1 { s.flip() } else { s };
+                Self::Cubic(CubicBezierSegment { from, to, ..s })
+            }
+        }
+    }
+
+    fn tangent(&self, t: f64) -> Vector2D<f64> {
+        // The derivative vanishes where a control point coincides with an end
+        // point, so fall back to a secant through nearby samples.
+        self.derivative(t)
+            .try_normalize()
+            .or_else(|| {
+                (self.sample((t + 1e-3).min(1.0)) - self.sample((t - 1e-3).max(0.0)))
+                    .try_normalize()
+            })
+            .unwrap_or_else(Vector2D::zero)
+    }
+}
+
+/// The curves of a path with the distance along the path at which each one
+/// ends, so that many points along it can be looked up without measuring the
+/// curves again.
+#[derive(Clone, Debug)]
+pub struct PathMeasure {
+    curves: Vec<Curve>,
+    ends: Vec<f64>,
+}
+
+impl PathMeasure {
+    pub fn new(path: &Path) -> Self {
+        let curves = path.curves();
+        let ends = curves
+            .iter()
+            .scan(0.0, |end, curve| {
+                *end += curve.length();
+                Some(*end)
+            })
+            .collect();
+        Self { curves, ends }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.ends.last().copied().unwrap_or(0.0)
+    }
+
+    pub fn point_and_tangent_at_length(
+        &self,
+        distance: f64,
+    ) -> Option<(Point2D<f64>, Vector2D<f64>)> {
+        let last = *self.curves.last()?;
+        let distance = distance.max(0.0);
+        let index = self.ends.partition_point(|&end| end < distance);
+        let Some(curve) = self.curves.get(index) else {
+            return Some((last.to(), last.tangent(1.0)));
+        };
+        let start = index.checked_sub(1).map_or(0.0, |index| self.ends[index]);
+        let t = curve.t_at_length(distance - start);
+        Some((curve.sample(t), curve.tangent(t)))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Subpath {
+    start: Point2D<f64>,
+    curves: Vec<Curve>,
+    closed: bool,
+}
+
+impl Subpath {
+    fn new(start: Point2D<f64>) -> Self {
+        Self {
+            start,
+            curves: Vec::new(),
+            closed: false,
+        }
+    }
+
+    /// Returns the start point followed by the end point of every piece of
+    /// the flattened subpath.
+    fn flattened_points(&self, tolerance: f64) -> Vec<Point2D<f64>> {
+        let mut points = vec![self.start];
+        for curve in &self.curves {
+            curve.for_each_flattened_with_t(tolerance, &mut |line, _| points.push(line.to));
+        }
+        points
+    }
+
+    fn end(&self) -> Point2D<f64> {
+        self.curves.last().map_or(self.start, Curve::to)
+    }
+}
+
+/// Returns which of `points` to keep so that no dropped point is further than
+/// `tolerance` from the simplified polyline. The end points are always kept.
+fn douglas_peucker(points: &[Point2D<f64>], tolerance: f64) -> Vec<bool> {
+    let mut keep = vec![false; points.len()];
+    let Some(last) = points.len().checked_sub(1) else {
+        return keep;
+    };
+    keep[0] = true;
+    keep[last] = true;
+    let mut stack = vec![(0, last)];
+    while let Some((a, b)) = stack.pop() {
+        let segment = LineSegment {
+            from: points[a],
+            to: points[b],
+        };
+        let farthest = (a + 1..b)
+            .map(|i| (i, segment.square_distance_to_point(points[i])))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((i, square_distance)) = farthest
+            && square_distance > tolerance * tolerance
+        {
+            keep[i] = true;
+            stack.push((a, i));
+            stack.push((i, b));
+        }
+    }
+    keep
+}
+
+const BOOLEAN_EPSILON: f64 = 1e-9;
+
+type PointKey = (u64, u64);
+
+fn point_key(p: Point2D<f64>) -> PointKey {
+    // Adding zero turns negative zero into positive zero.
+    ((p.x + 0.0).to_bits(), (p.y + 0.0).to_bits())
+}
+
+/// A flattened piece of a curve, remembering which curve and which parameter
+/// range of it the piece came from so that the curve can be recovered.
+#[derive(Clone, Copy, Debug)]
+struct BooleanEdge {
+    from: Point2D<f64>,
+    to: Point2D<f64>,
+    operand: usize,
+    curve: usize,
+    t0: f64,
+    t1: f64,
+}
+
+impl BooleanEdge {
+    fn reversed(self) -> Self {
+        Self {
+            from: self.to,
+            to: self.from,
+            t0: self.t1,
+            t1: self.t0,
+            ..self
+        }
+    }
+
+    fn key(&self) -> (PointKey, PointKey) {
+        let (a, b) = (point_key(self.from), point_key(self.to));
+        if a <= b { (a, b) } else { (b, a) }
+    }
+
+    fn continues(&self, prev: &Self) -> bool {
+        self.curve == prev.curve
+            && (self.t0 - prev.t1).abs() <= BOOLEAN_EPSILON
+            && (self.t1 > self.t0) == (prev.t1 > prev.t0)
+    }
+}
+
+fn intersect_boolean_edges(
+    a: &BooleanEdge,
+    b: &BooleanEdge,
+    a_splits: &mut Vec<(f64, Point2D<f64>)>,
+    b_splits: &mut Vec<(f64, Point2D<f64>)>,
+) {
+    let is_interior = |t: f64| BOOLEAN_EPSILON < t && t < 1.0 - BOOLEAN_EPSILON;
+    let d1 = a.to - a.from;
+    let d2 = b.to - b.from;
+    let r = b.from - a.from;
+    let denom = d1.cross(d2);
+    if denom.abs() > BOOLEAN_EPSILON * d1.length() * d2.length() {
+        let t = r.cross(d2) / denom;
+        let s = r.cross(d1) / denom;
+        let range = -BOOLEAN_EPSILON..=1.0 + BOOLEAN_EPSILON;
+        if !(range.contains(&t) && range.contains(&s)) {
+            return;
+        }
+        // Prefer existing end points so that touching edges share vertices
+        // exactly.
+        let p = if s <= BOOLEAN_EPSILON {
+            b.from
+        } else if s >= 1.0 - BOOLEAN_EPSILON {
+            b.to
+        } else if t <= BOOLEAN_EPSILON {
+            a.from
+        } else if t >= 1.0 - BOOLEAN_EPSILON {
+            a.to
+        } else {
+            a.from + d1 * t
+        };
+        if is_interior(t) {
+            a_splits.push((t, p));
+        }
+        if is_interior(s) {
+            b_splits.push((s, p));
+        }
+    } else if r.cross(d1).abs() <= BOOLEAN_EPSILON * d1.length() {
+        for q in [b.from, b.to] {
+            let t = (q - a.from).dot(d1) / d1.square_length();
+            if is_interior(t) {
+                a_splits.push((t, q));
+            }
+        }
+        for q in [a.from, a.to] {
+            let s = (q - b.from).dot(d2) / d2.square_length();
+            if is_interior(s) {
+                b_splits.push((s, q));
+            }
+        }
+    }
+}
+
+/// Splits the edges at every point where they cross or touch each other, so
+/// that any two of the resulting edges are either identical or meet only at
+/// their end points.
+fn split_boolean_edges(edges: &[BooleanEdge]) -> Vec<BooleanEdge> {
+    let mut splits = vec![Vec::new(); edges.len()];
+    let mut order = (0..edges.len()).collect::<Vec<_>>();
+    order.sort_by(|&i, &j| {
+        let (a, b) = (&edges[i], &edges[j]);
+        a.from.x.min(a.to.x).total_cmp(&b.from.x.min(b.to.x))
+    });
+    for (n, &i) in order.iter().enumerate() {
+        let a = &edges[i];
+        let a_box = Box2D::from_points([a.from, a.to]).inflate(BOOLEAN_EPSILON, BOOLEAN_EPSILON);
+        for &j in &order[n + 1..] {
+            let b = &edges[j];
+            let b_box = Box2D::from_points([b.from, b.to]);
+            if b_box.min.x > a_box.max.x {
+                break;
+            }
+            if b_box.min.y > a_box.max.y || b_box.max.y < a_box.min.y {
+                continue;
+            }
+            let (a_splits, b_splits) = if i < j {
+                let (left, right) = splits.split_at_mut(j);
+                (&mut left[i], &mut right[0])
+            } else {
+                let (left, right) = splits.split_at_mut(i);
+                (&mut right[0], &mut left[j])
+            };
+            intersect_boolean_edges(a, b, a_splits, b_splits);
+        }
+    }
+    let mut result = Vec::with_capacity(edges.len());
+    for (edge, mut splits) in edges.iter().zip(splits) {
+        splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut from = edge.from;
+        let mut t0 = edge.t0;
+        for (t, to) in splits.into_iter().chain([(1.0, edge.to)]) {
+            if to == from {
+                continue;
+            }
+            let t1 = edge.t0 + (edge.t1 - edge.t0) * t;
+            result.push(BooleanEdge {
+                from,
+                to,
+                t0,
+                t1,
+                ..*edge
+            });
+            from = to;
+            t0 = t1;
+        }
+    }
+    result
+}
+
+/// Returns the winding numbers of both operands just to the right and just to
+/// the left of each group of edges with the same end points.
+///
+/// The windings come from a ray cast from the midpoint of the group's first
+/// edge: horizontally for steep edges and vertically for flat ones, so that
+/// the ray never runs along the edge. Queries and edges are swept along the
+/// axis across the ray, so each ray only tests the edges whose span contains
+/// its origin.
+fn classify_boolean_edges(
+    edges: &[BooleanEdge],
+    groups: &[Vec<usize>],
+    edge_groups: &[usize],
+) -> Vec<([i32; 2], [i32; 2])> {
+    let mut result = vec![([0; 2], [0; 2]); groups.len()];
+    // Rotating by a quarter turn keeps the orientation, so windings counted in
+    // either frame agree.
+    let frames: [fn(Point2D<f64>) -> Point2D<f64>; 2] = [|p| p, |p| point2(p.y, -p.x)];
+    for (steep, frame) in [true, false].into_iter().zip(frames) {
+        let mut queries = groups
+            .iter()
+            .enumerate()
+            .filter_map(|(group_index, group)| {
+                let edge = &edges[group[0]];
+                let d = edge.to - edge.from;
+                (steep == (d.y.abs() >= d.x.abs()))
+                    .then(|| (group_index, frame(edge.from.lerp(edge.to, 0.5))))
+            })
+            .collect::<Vec<_>>();
+        if queries.is_empty() {
+            continue;
+        }
+        queries.sort_by(|a, b| a.1.y.total_cmp(&b.1.y));
+        let ends = edges
+            .iter()
+            .map(|edge| (frame(edge.from), frame(edge.to)))
+            .collect::<Vec<_>>();
+        let min_y = |i: usize| ends[i].0.y.min(ends[i].1.y);
+        let max_y = |i: usize| ends[i].0.y.max(ends[i].1.y);
+        let mut by_min_y = (0..edges.len()).collect::<Vec<_>>();
+        by_min_y.sort_by(|&i, &j| min_y(i).total_cmp(&min_y(j)));
+        let mut entering = by_min_y.into_iter().peekable();
+        let mut active = Vec::new();
+        for (group_index, m) in queries {
+            while let Some(i) = entering.next_if(|&i| min_y(i) <= m.y) {
+                active.push(i);
+            }
+            active.retain(|&i| max_y(i) > m.y);
+            // Cast a ray from `m` towards +x.
+            let mut winding = [0; 2];
+            for &i in &active {
+                if edge_groups[i] == group_index {
+                    continue;
+                }
+                let a = ends[i].0 - m.to_vector();
+                let b = ends[i].1 - m.to_vector();
+                let is_left = a.x * (b.y - a.y) - a.y * (b.x - a.x);
+                if a.y <= 0.0 {
+                    if b.y > 0.0 && is_left > 0.0 {
+                        winding[edges[i].operand] += 1;
+                    }
+                } else if b.y <= 0.0 && is_left < 0.0 {
+                    winding[edges[i].operand] -= 1;
+                }
+            }
+            let group = &groups[group_index];
+            let edge = &edges[group[0]];
+            let u = edge.to - edge.from;
+            let mut crossing = [0; 2];
+            for other in group.iter().map(|&i| &edges[i]) {
+                crossing[other.operand] += if (other.to - other.from).dot(u) > 0.0 {
+                    1
+                } else {
+                    -1
+                };
+            }
+            let add =
+                |a: [i32; 2], sign: i32| [a[0] + sign * crossing[0], a[1] + sign * crossing[1]];
+            // The ray leaves from the right of the edge when the edge points
+            // towards +y in this frame.
+            result[group_index] = if frame(u.to_point()).y > 0.0 {
+                (winding, add(winding, 1))
+            } else {
+                (add(winding, -1), winding)
+            };
+        }
+    }
+    result
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Path {
     ops: Vec<PathOp>,
@@ -242,6 +747,238 @@ impl Path {
         path
     }
 
+    /// Serializes the path as SVG path data. With `precision`, coordinates
+    /// are rounded to that many decimal places; otherwise they round-trip
+    /// exactly. Empty subpaths are dropped.
+    pub fn to_svg(&self, precision: Option<usize>, relative: bool) -> String {
+        struct Writer {
+            out: String,
+            precision: Option<usize>,
+            relative: bool,
+            last_command: Option<char>,
+            last_number_has_dot: Option<bool>,
+        }
+
+        impl Writer {
+            fn format(&self, value: f64) -> String {
+                let mut s = match self.precision {
+                    Some(precision) => format!("{value:.precision$}"),
+                    None => value.to_string(),
+                };
+                if s.contains('.') {
+                    s.truncate(s.trim_end_matches('0').trim_end_matches('.').len());
+                }
+                if s == "-0" {
+                    s = "0".into();
+                }
+                if let Some(rest) = s.strip_prefix("0.") {
+                    s = format!(".{rest}");
+                } else if let Some(rest) = s.strip_prefix("-0.") {
+                    s = format!("-.{rest}");
+                }
+                s
+            }
+
+            fn command(&mut self, command: char, implicit_next: char) {
+                let command = if self.relative {
+                    command.to_ascii_lowercase()
+                } else {
+                    command
+                };
+                if self.last_command != Some(command) {
+                    self.out.push(command);
+                    self.last_number_has_dot = None;
+                }
+                self.last_command = Some(if self.relative {
+                    implicit_next.to_ascii_lowercase()
+                } else {
+                    implicit_next
+                });
+            }
+
+            /// Writes `value` relative to `origin` and returns the value a
+            /// parser will read back.
+            fn number(&mut self, value: f64, origin: f64) -> f64 {
+                let origin = if self.relative { origin } else { 0.0 };
+                let s = self.format(value - origin);
+                let needs_separator = match self.last_number_has_dot {
+                    Some(has_dot) => !(s.starts_with('-') || has_dot && s.starts_with('.')),
+                    None => false,
+                };
+                if needs_separator {
+                    self.out.push(' ');
+                }
+                self.out.push_str(&s);
+                self.last_number_has_dot = Some(s.contains('.'));
+                origin + s.parse::<f64>().unwrap()
+            }
+
+            fn peek(&self, value: f64, origin: f64) -> f64 {
+                let origin = if self.relative { origin } else { 0.0 };
+                origin + self.format(value - origin).parse::<f64>().unwrap()
+            }
+
+            fn point(&mut self, p: Point2D<f64>, origin: Point2D<f64>) -> Point2D<f64> {
+                let x = self.number(p.x, origin.x);
+                let y = self.number(p.y, origin.y);
+                point2(x, y)
+            }
+        }
+
+        let mut writer = Writer {
+            out: String::new(),
+            precision,
+            relative,
+            last_command: None,
+            last_number_has_dot: None,
+        };
+        let mut cursor = None;
+        let mut start = Point2D::zero();
+        for (i, op) in self.ops.iter().enumerate() {
+            let origin = cursor.unwrap_or_default();
+            cursor = Some(match *op {
+                PathOp::MoveTo { p } => {
+                    if matches!(self.ops.get(i + 1), None | Some(PathOp::MoveTo { .. })) {
+                        continue;
+                    }
+                    writer.command('M', 'L');
+                    start = writer.point(p, origin);
+                    start
+                }
+                PathOp::LineTo { p } if cursor.is_none() => {
+                    writer.command('M', 'L');
+                    start = writer.point(p, origin);
+                    start
+                }
+                PathOp::LineTo { p } if writer.peek(p.y, origin.y) == origin.y => {
+                    writer.command('H', 'H');
+                    point2(writer.number(p.x, origin.x), origin.y)
+                }
+                PathOp::LineTo { p } if writer.peek(p.x, origin.x) == origin.x => {
+                    writer.command('V', 'V');
+                    point2(origin.x, writer.number(p.y, origin.y))
+                }
+                PathOp::LineTo { p } => {
+                    writer.command('L', 'L');
+                    writer.point(p, origin)
+                }
+                PathOp::QuadTo { c, p } => {
+                    writer.command('Q', 'Q');
+                    writer.point(c, origin);
+                    writer.point(p, origin)
+                }
+                PathOp::CubicTo { c1, c2, p } => {
+                    writer.command('C', 'C');
+                    writer.point(c1, origin);
+                    writer.point(c2, origin);
+                    writer.point(p, origin)
+                }
+                PathOp::Close => {
+                    if cursor.is_none() {
+                        continue;
+                    }
+                    writer.command('Z', 'Z');
+                    start
+                }
+            });
+        }
+        writer.out
+    }
+
+    /// Encodes the path as a little-endian `u32` operation count, one byte
+    /// per operation, padding to a multiple of eight bytes, and then the
+    /// `f64` coordinates of all operations.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut verbs = Vec::with_capacity(self.ops.len());
+        let mut coords = Vec::new();
+        for op in &self.ops {
+            match *op {
+                PathOp::MoveTo { p } => {
+                    verbs.push(0);
+                    coords.extend([p.x, p.y]);
+                }
+                PathOp::LineTo { p } => {
+                    verbs.push(1);
+                    coords.extend([p.x, p.y]);
+                }
+                PathOp::QuadTo { c, p } => {
+                    verbs.push(2);
+                    coords.extend([c.x, c.y, p.x, p.y]);
+                }
+                PathOp::CubicTo { c1, c2, p } => {
+                    verbs.push(3);
+                    coords.extend([c1.x, c1.y, c2.x, c2.y, p.x, p.y]);
+                }
+                PathOp::Close => verbs.push(4),
+            }
+        }
+        let coords_start = (4 + verbs.len()).next_multiple_of(8);
+        let mut buf = Vec::with_capacity(coords_start + coords.len() * 8);
+        buf.extend((verbs.len() as u32).to_le_bytes());
+        buf.extend(verbs);
+        buf.resize(coords_start, 0);
+        for value in coords {
+            buf.extend(value.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Decodes a path encoded by [`Path::to_bytes`]. Truncated or trailing
+    /// data, unknown operations and non-finite coordinates are rejected.
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, Canvas2DError> {
+        fn next_point(coords: &mut impl Iterator<Item = f64>) -> Option<Point2D<f64>> {
+            let p = point2(coords.next()?, coords.next()?);
+            (p.x.is_finite() && p.y.is_finite()).then_some(p)
+        }
+
+        fn decode(buf: &[u8]) -> Option<Path> {
+            let (count, rest) = buf.split_first_chunk::<4>()?;
+            let count = u32::from_le_bytes(*count) as usize;
+            let verbs = rest.get(..count)?;
+            let (coords, []) = buf.get((4 + count).next_multiple_of(8)..)?.as_chunks::<8>() else {
+                return None;
+            };
+            let mut coords = coords.iter().map(|&value| f64::from_le_bytes(value));
+            let mut path = Path::new();
+            for &verb in verbs {
+                match verb {
+                    0 => path.do_move_to(next_point(&mut coords)?),
+                    1 => path.do_line_to(next_point(&mut coords)?),
+                    2 => {
+                        let c = next_point(&mut coords)?;
+                        path.do_quad_to(c, next_point(&mut coords)?);
+                    }
+                    3 => {
+                        let c1 = next_point(&mut coords)?;
+                        let c2 = next_point(&mut coords)?;
+                        path.do_cubic_to(c1, c2, next_point(&mut coords)?);
+                    }
+                    4 => path.do_close(),
+                    _ => return None,
+                }
+            }
+            coords.next().is_none().then_some(path)
+        }
+
+        decode(buf).ok_or(Canvas2DError::DecodePath)
+    }
+
+    pub fn from_raqote(path: &raqote::Path) -> Self {
+        let mut result = Self::new();
+        for op in &path.ops {
+            match *op {
+                raqote::PathOp::MoveTo(p) => result.do_move_to(p.cast()),
+                raqote::PathOp::LineTo(p) => result.do_line_to(p.cast()),
+                raqote::PathOp::QuadTo(c, p) => result.do_quad_to(c.cast(), p.cast()),
+                raqote::PathOp::CubicTo(c1, c2, p) => {
+                    result.do_cubic_to(c1.cast(), c2.cast(), p.cast())
+                }
+                raqote::PathOp::Close => result.do_close(),
+            }
+        }
+        result
+    }
+
     pub fn clear(&mut self) {
         self.ops.clear();
     }
@@ -282,6 +1019,14 @@ impl Path {
         arc.for_each_quadratic_bezier(&mut |s| self.do_quad_to(s.ctrl, s.to));
     }
 
+    fn push_curve(&mut self, curve: Curve) {
+        match curve {
+            Curve::Line(s) => self.do_line_to(s.to),
+            Curve::Quad(s) => self.do_quad_to(s.ctrl, s.to),
+            Curve::Cubic(s) => self.do_cubic_to(s.ctrl1, s.ctrl2, s.to),
+        }
+    }
+
     fn do_close(&mut self) {
         self.ops.push(PathOp::Close);
     }
@@ -456,6 +1201,435 @@ impl Path {
         }
     }
 
+    pub fn control_bounds(&self) -> Option<Box2D<f64>> {
+        let mut points = self
+            .ops
+            .iter()
+            .flat_map(|op| match *op {
+                PathOp::MoveTo { p } | PathOp::LineTo { p } => [Some(p), None, None],
+                PathOp::QuadTo { c, p } => [Some(c), Some(p), None],
+                PathOp::CubicTo { c1, c2, p } => [Some(c1), Some(c2), Some(p)],
+                PathOp::Close => [None, None, None],
+            })
+            .flatten();
+        let first = points.next()?;
+        Some(points.fold(Box2D::new(first, first), |b, p| {
+            Box2D::new(b.min.min(p), b.max.max(p))
+        }))
+    }
+
+    /// Splits the path into its non-empty subpaths. A closed subpath gets an
+    /// explicit closing line unless it already ends at its start point.
+    fn subpaths(&self) -> Vec<Subpath> {
+        let mut subpaths = Vec::new();
+        let mut current: Option<Subpath> = None;
+        let mut next_start = None;
+        for op in &self.ops {
+            let subpath = match *op {
+                PathOp::MoveTo { p } => {
+                    subpaths.extend(current.replace(Subpath::new(p)));
+                    continue;
+                }
+                PathOp::LineTo { p }
+                | PathOp::QuadTo { c: p, .. }
+                | PathOp::CubicTo { c1: p, .. } => {
+                    current.get_or_insert_with(|| Subpath::new(next_start.unwrap_or(p)))
+                }
+                PathOp::Close => {
+                    if let Some(mut subpath) = current.take() {
+                        let (from, to) = (subpath.end(), subpath.start);
+                        if from != to {
+                            subpath.curves.push(Curve::Line(LineSegment { from, to }));
+                        }
+                        subpath.closed = true;
+                        next_start = Some(to);
+                        subpaths.push(subpath);
+                    }
+                    continue;
+                }
+            };
+            let from = subpath.end();
+            subpath.curves.push(match *op {
+                PathOp::LineTo { p } => Curve::Line(LineSegment { from, to: p }),
+                PathOp::QuadTo { c, p } => Curve::Quad(QuadraticBezierSegment {
+                    from,
+                    ctrl: c,
+                    to: p,
+                }),
+                PathOp::CubicTo { c1, c2, p } => Curve::Cubic(CubicBezierSegment {
+                    from,
+                    ctrl1: c1,
+                    ctrl2: c2,
+                    to: p,
+                }),
+                PathOp::MoveTo { .. } | PathOp::Close => unreachable!(),
+            });
+        }
+        subpaths.extend(current);
+        subpaths.retain(|subpath| !subpath.curves.is_empty());
+        subpaths
+    }
+
+    fn curves(&self) -> Vec<Curve> {
+        self.subpaths()
+            .into_iter()
+            .flat_map(|subpath| subpath.curves)
+            .collect()
+    }
+
+    pub fn subpath_count(&self) -> usize {
+        self.subpaths().len()
+    }
+
+    pub fn tight_bounds(&self) -> Option<Box2D<f64>> {
+        self.curves()
+            .iter()
+            .map(Curve::bounding_box)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn length(&self) -> f64 {
+        self.curves().iter().map(Curve::length).sum()
+    }
+
+    pub fn point_and_tangent_at_length(
+        &self,
+        distance: f64,
+    ) -> Option<(Point2D<f64>, Vector2D<f64>)> {
+        PathMeasure::new(self).point_and_tangent_at_length(distance)
+    }
+
+    /// Splits every subpath into the pieces that are "on" in the given dash
+    /// pattern, restarting the pattern at the start of each subpath. Curves
+    /// stay curves.
+    pub fn dash(&self, dash_list: &[f64], dash_offset: f64) -> Self {
+        let pattern = if dash_list.len() % 2 == 1 {
+            [dash_list, dash_list].concat()
+        } else {
+            dash_list.to_vec()
+        };
+        let pattern_width = pattern.iter().sum::<f64>();
+        if !(pattern_width > 0.0 && pattern_width.is_finite() && dash_offset.is_finite()) {
+            return self.clone();
+        }
+        let mut path = Self::new();
+        for subpath in self.subpaths() {
+            let mut index = 0;
+            let mut offset = dash_offset.rem_euclid(pattern_width);
+            while offset >= pattern[index] {
+                offset -= pattern[index];
+                index = (index + 1) % pattern.len();
+            }
+            let mut remaining = pattern[index] - offset;
+            let mut is_drawing = false;
+            for curve in &subpath.curves {
+                let length = curve.length();
+                let mut position = 0.0;
+                while position < length {
+                    let step = remaining.min(length - position);
+                    if index % 2 == 0 {
+                        let t0 = curve.t_at_length(position);
+                        let t1 = curve.t_at_length(position + step);
+                        let piece = curve.fit(t0, t1, curve.sample(t0), curve.sample(t1));
+                        if !is_drawing {
+                            path.do_move_to(piece.from());
+                            is_drawing = true;
+                        }
+                        path.push_curve(piece);
+                    }
+                    position += step;
+                    remaining -= step;
+                    if remaining <= 0.0 {
+                        index = (index + 1) % pattern.len();
+                        remaining = pattern[index];
+                        is_drawing = false;
+                    }
+                }
+            }
+        }
+        path
+    }
+
+    /// Replaces every curve with line segments that stay within `tolerance` of
+    /// it.
+    pub fn flatten(&self, tolerance: f64) -> Self {
+        let mut path = Self::new();
+        for subpath in self.subpaths() {
+            let mut points = subpath.flattened_points(tolerance);
+            if subpath.closed && points.last() == Some(&subpath.start) {
+                points.pop();
+            }
+            path.do_move_to(subpath.start);
+            for &p in &points[1..] {
+                path.do_line_to(p);
+            }
+            if subpath.closed {
+                path.do_close();
+            }
+        }
+        path
+    }
+
+    /// Removes points from runs of consecutive line segments with the
+    /// Douglas–Peucker algorithm. Curves are kept as they are.
+    pub fn simplify(&self, tolerance: f64) -> Self {
+        let mut path = Self::new();
+        for subpath in self.subpaths() {
+            path.do_move_to(subpath.start);
+            let mut run = vec![subpath.start];
+            for curve in &subpath.curves {
+                if let Curve::Line(s) = curve {
+                    run.push(s.to);
+                    continue;
+                }
+                path.push_simplified_run(&run, tolerance);
+                path.push_curve(*curve);
+                run = vec![curve.to()];
+            }
+            path.push_simplified_run(&run, tolerance);
+            if subpath.closed {
+                path.do_close();
+            }
+        }
+        path
+    }
+
+    fn push_simplified_run(&mut self, points: &[Point2D<f64>], tolerance: f64) {
+        let keep = douglas_peucker(points, tolerance);
+        for (&p, keep) in points.iter().zip(keep).skip(1) {
+            if keep {
+                self.do_line_to(p);
+            }
+        }
+    }
+
+    /// Grows (for positive `distance`) or shrinks (for negative `distance`)
+    /// the area that the path fills with `fill_rule`. Every edge of the
+    /// outline is moved along its normal and the gaps at convex corners are
+    /// filled with `join`, with miters longer than `miter_limit` times
+    /// `distance` cut to bevels. Curves and round joins are flattened with
+    /// `tolerance`.
+    pub fn offset(
+        &self,
+        distance: f64,
+        fill_rule: CanvasFillRule,
+        join: CanvasLineJoin,
+        miter_limit: f64,
+        tolerance: f64,
+    ) -> Self {
+        if distance == 0.0 || !distance.is_finite() {
+            return self.clone();
+        }
+        // Resolving the overlaps first leaves contours that never cross and
+        // have the filled area on their left, so that the right normal of
+        // every edge points away from it.
+        let outline = self.flatten(tolerance).boolean_op(
+            fill_rule,
+            &Self::new(),
+            CanvasFillRule::NonZero,
+            PathBooleanOperation::Union,
+        );
+        let mut path = Self::new();
+        for subpath in outline.subpaths() {
+            let mut points = subpath.flattened_points(tolerance);
+            points.dedup();
+            if points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            if points.len() < 3 {
+                continue;
+            }
+            let normals = (0..points.len())
+                .map(|i| {
+                    let d = (points[(i + 1) % points.len()] - points[i]).normalize();
+                    vec2(d.y, -d.x)
+                })
+                .collect::<Vec<_>>();
+            let mut offset = Vec::with_capacity(points.len() * 2);
+            for (i, &p) in points.iter().enumerate() {
+                let n0 = normals[(i + points.len() - 1) % points.len()];
+                let n1 = normals[i];
+                let (a, b) = (p + n0 * distance, p + n1 * distance);
+                let cos = n0.dot(n1);
+                offset.push(a);
+                if n0.cross(n1) * distance <= 0.0 {
+                    // The offset edges overlap here. Routing the outline back
+                    // through the vertex gives the overlap a winding number
+                    // that the cleanup below drops; nearly straight corners
+                    // only leave a sliver that it drops as well.
+                    if cos < 0.99 {
+                        offset.push(p);
+                    }
+                    offset.push(b);
+                    continue;
+                }
+                match join {
+                    CanvasLineJoin::Round => {
+                        let arc = Arc {
+                            center: p,
+                            radii: vec2(distance.abs(), distance.abs()),
+                            start_angle: (a - p).angle_from_x_axis(),
+                            sweep_angle: Angle::radians(n0.cross(n1).atan2(cos)),
+                            x_rotation: Angle::zero(),
+                        };
+                        arc.for_each_flattened(tolerance, &mut |line| offset.push(line.to));
+                        offset.pop();
+                    }
+                    CanvasLineJoin::Miter if 2.0 / (1.0 + cos) <= miter_limit * miter_limit => {
+                        offset.push(p + (n0 + n1) * (distance / (1.0 + cos)));
+                    }
+                    _ => {}
+                }
+                offset.push(b);
+            }
+            path.do_move_to(offset[0]);
+            for &p in &offset[1..] {
+                path.do_line_to(p);
+            }
+            path.do_close();
+        }
+        path.combine(&Self::new(), |winding| winding[0] > 0)
+    }
+
+    fn flatten_for_boolean(
+        &self,
+        operand: usize,
+        curves: &mut Vec<Curve>,
+        edges: &mut Vec<BooleanEdge>,
+    ) {
+        for subpath in self.subpaths() {
+            let (from, to) = (subpath.end(), subpath.start);
+            let closing_line = (from != to).then_some(Curve::Line(LineSegment { from, to }));
+            for curve in subpath.curves.into_iter().chain(closing_line) {
+                let index = curves.len();
+                curves.push(curve);
+                curve.for_each_flattened_with_t(CURVE_TOLERANCE, &mut |line, t| {
+                    if line.from != line.to {
+                        edges.push(BooleanEdge {
+                            from: line.from,
+                            to: line.to,
+                            operand,
+                            curve: index,
+                            t0: t.start,
+                            t1: t.end,
+                        });
+                    }
+                });
+            }
+        }
+    }
+
+    /// Combines the areas that this path and `other` would fill with the given
+    /// fill rules. The resulting contours never overlap and are oriented so
+    /// that either fill rule fills the same area. Parts of the boundary that
+    /// come from a single curve of either operand are emitted as that curve;
+    /// the rest stays flattened.
+    pub fn boolean_op(
+        &self,
+        fill_rule: CanvasFillRule,
+        other: &Path,
+        other_fill_rule: CanvasFillRule,
+        operation: PathBooleanOperation,
+    ) -> Self {
+        let fill_rules = [fill_rule, other_fill_rule];
+        self.combine(other, |winding| {
+            operation.apply(
+                fill_rules[0].contains(winding[0]),
+                fill_rules[1].contains(winding[1]),
+            )
+        })
+    }
+
+    /// Traces the boundary of the area where `is_inside` holds for the
+    /// winding numbers of this path and `other`.
+    fn combine(&self, other: &Path, is_inside: impl Fn([i32; 2]) -> bool) -> Self {
+        let mut curves = Vec::new();
+        let mut edges = Vec::new();
+        self.flatten_for_boolean(0, &mut curves, &mut edges);
+        other.flatten_for_boolean(1, &mut curves, &mut edges);
+        let edges = split_boolean_edges(&edges);
+
+        let mut groups = Vec::<Vec<usize>>::new();
+        let mut group_indices = HashMap::new();
+        let edge_groups = edges
+            .iter()
+            .enumerate()
+            .map(|(i, edge)| {
+                let index = *group_indices.entry(edge.key()).or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+                groups[index].push(i);
+                index
+            })
+            .collect::<Vec<_>>();
+        let mut kept = Vec::new();
+        let windings = classify_boolean_edges(&edges, &groups, &edge_groups);
+        for (group, (right, left)) in groups.iter().zip(windings) {
+            let edge = edges[group[0]];
+            match (is_inside(left), is_inside(right)) {
+                (true, false) => kept.push(edge),
+                (false, true) => kept.push(edge.reversed()),
+                _ => {}
+            }
+        }
+
+        let mut outgoing = HashMap::<PointKey, Vec<usize>>::new();
+        for (i, edge) in kept.iter().enumerate() {
+            outgoing.entry(point_key(edge.from)).or_default().push(i);
+        }
+        let mut used = vec![false; kept.len()];
+        let mut path = Self::new();
+        for start in 0..kept.len() {
+            if used[start] {
+                continue;
+            }
+            used[start] = true;
+            let mut contour = vec![kept[start]];
+            let origin = point_key(kept[start].from);
+            loop {
+                let current = contour.last().unwrap();
+                if point_key(current.to) == origin {
+                    break;
+                }
+                let next = outgoing.get(&point_key(current.to)).and_then(|candidates| {
+                    candidates
+                        .iter()
+                        .copied()
+                        .filter(|&i| !used[i])
+                        .max_by_key(|&i| kept[i].continues(current))
+                });
+                let Some(next) = next else {
+                    break;
+                };
+                used[next] = true;
+                contour.push(kept[next]);
+            }
+            path.push_boolean_contour(&curves, contour);
+        }
+        path
+    }
+
+    fn push_boolean_contour(&mut self, curves: &[Curve], mut contour: Vec<BooleanEdge>) {
+        let len = contour.len();
+        if let Some(start) =
+            (0..len).find(|&i| !contour[i].continues(&contour[(i + len - 1) % len]))
+        {
+            contour.rotate_left(start);
+        }
+        self.do_move_to(contour[0].from);
+        let mut run_start = 0;
+        for i in 1..=len {
+            if i < len && contour[i].continues(&contour[i - 1]) {
+                continue;
+            }
+            let (first, last) = (&contour[run_start], &contour[i - 1]);
+            self.push_curve(curves[first.curve].fit(first.t0, last.t1, first.from, last.to));
+            run_start = i;
+        }
+        self.do_close();
+    }
+
     pub fn to_raqote_ops(&self) -> Vec<raqote::PathOp> {
         self.ops
             .iter()
@@ -500,6 +1674,15 @@ pub fn op_canvas_2d_path_from_svg(#[string] path_data: &str) -> Wrap<RefCell<Pat
     Wrap::new(RefCell::new(Path::from_svg(path_data)))
 }
 
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_path_from_bytes(
+    #[anybuffer] buf: &[u8],
+) -> Result<Wrap<RefCell<Path>>, Canvas2DError> {
+    let path = Path::from_bytes(buf)?;
+    Ok(Wrap::new(RefCell::new(path)))
+}
+
 #[op2]
 #[cppgc]
 pub fn op_canvas_2d_path_clone(#[cppgc] this: &Wrap<RefCell<Path>>) -> Wrap<RefCell<Path>> {
@@ -653,8 +1836,470 @@ pub fn op_canvas_2d_path_round_rect(
     }
 }
 
+#[op2(fast)]
+pub fn op_canvas_2d_path_get_tight_bounds(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    #[buffer] out: &mut [f64],
+) -> bool {
+    let this = this.borrow();
+    match this.tight_bounds() {
+        Some(bounds) => {
+            out.copy_from_slice(&[bounds.min.x, bounds.min.y, bounds.width(), bounds.height()]);
+            true
+        }
+        None => false,
+    }
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_path_get_control_bounds(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    #[buffer] out: &mut [f64],
+) -> bool {
+    let this = this.borrow();
+    match this.control_bounds() {
+        Some(bounds) => {
+            out.copy_from_slice(&[bounds.min.x, bounds.min.y, bounds.width(), bounds.height()]);
+            true
+        }
+        None => false,
+    }
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_path_get_length(#[cppgc] this: &Wrap<RefCell<Path>>) -> f64 {
+    let this = this.borrow();
+    this.length()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_path_get_point_at_length(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    distance: f64,
+    #[buffer] out: &mut [f64],
+) -> bool {
+    let this = this.borrow();
+    if distance.is_nan() {
+        return false;
+    }
+    match this.point_and_tangent_at_length(distance) {
+        Some((p, v)) => {
+            out.copy_from_slice(&[p.x, p.y, v.x, v.y]);
+            true
+        }
+        None => false,
+    }
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_path_get_subpath_count(#[cppgc] this: &Wrap<RefCell<Path>>) -> u32 {
+    let this = this.borrow();
+    this.subpath_count() as u32
+}
+
+#[op2]
+#[string]
+pub fn op_canvas_2d_path_to_svg(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    precision: i32,
+    relative: bool,
+) -> String {
+    let this = this.borrow();
+    this.to_svg(usize::try_from(precision).ok(), relative)
+}
+
+#[op2]
+#[buffer]
+pub fn op_canvas_2d_path_to_bytes(#[cppgc] this: &Wrap<RefCell<Path>>) -> Vec<u8> {
+    let this = this.borrow();
+    this.to_bytes()
+}
+
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_path_dash(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    #[buffer] dash_list: &[f64],
+    dash_offset: f64,
+) -> Wrap<RefCell<Path>> {
+    let this = this.borrow();
+    Wrap::new(RefCell::new(this.dash(dash_list, dash_offset)))
+}
+
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_path_flatten(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    tolerance: f64,
+) -> Wrap<RefCell<Path>> {
+    let this = this.borrow();
+    Wrap::new(RefCell::new(this.flatten(tolerance.max(MIN_TOLERANCE))))
+}
+
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_path_simplify(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    tolerance: f64,
+) -> Wrap<RefCell<Path>> {
+    let this = this.borrow();
+    Wrap::new(RefCell::new(this.simplify(tolerance.max(0.0))))
+}
+
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_path_offset(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    distance: f64,
+    fill_rule: i32,
+    join: i32,
+    miter_limit: f64,
+    tolerance: f64,
+) -> Wrap<RefCell<Path>> {
+    let this = this.borrow();
+    let fill_rule = CanvasFillRule::from_repr(fill_rule).unwrap();
+    let join = CanvasLineJoin::from_repr(join).unwrap();
+    Wrap::new(RefCell::new(this.offset(
+        distance,
+        fill_rule,
+        join,
+        miter_limit,
+        tolerance.max(MIN_TOLERANCE),
+    )))
+}
+
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_path_boolean_op(
+    #[cppgc] this: &Wrap<RefCell<Path>>,
+    fill_rule: i32,
+    #[cppgc] other: &Wrap<RefCell<Path>>,
+    other_fill_rule: i32,
+    operation: i32,
+) -> Wrap<RefCell<Path>> {
+    let this = this.borrow();
+    let other = other.borrow();
+    let fill_rule = CanvasFillRule::from_repr(fill_rule).unwrap();
+    let other_fill_rule = CanvasFillRule::from_repr(other_fill_rule).unwrap();
+    let operation = PathBooleanOperation::from_repr(operation).unwrap();
+    Wrap::new(RefCell::new(this.boolean_op(
+        fill_rule,
+        &other,
+        other_fill_rule,
+        operation,
+    )))
+}
+
 #[op2(fast)]
 pub fn op_canvas_2d_path_close(#[cppgc] this: &Wrap<RefCell<Path>>) {
     let mut this = this.borrow_mut();
     this.close()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_path() -> Path {
+        let mut path = Path::new();
+        path.move_to(1.0, 2.0);
+        path.line_to(3.5, -4.0);
+        path.quad_to(5.0, 6.0, 7.25, 8.0);
+        path.cubic_to(9.0, 10.0, -11.0, 12.0, 13.0, 14.5);
+        path.close();
+        path.line_to(20.0, 0.0);
+        path.rect(30.0, 30.0, 10.0, 5.0);
+        path
+    }
+
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> Path {
+        let mut path = Path::new();
+        path.rect(x, y, w, h);
+        path
+    }
+
+    /// The edges of the flattened path, including those that close each
+    /// subpath when it is filled.
+    fn edges(path: &Path) -> Vec<LineSegment<f64>> {
+        let mut edges = Vec::new();
+        for subpath in path.flatten(0.01).subpaths() {
+            edges.extend(subpath.curves.iter().map(|curve| LineSegment {
+                from: curve.from(),
+                to: curve.to(),
+            }));
+            if !subpath.closed {
+                edges.push(LineSegment {
+                    from: subpath.end(),
+                    to: subpath.start,
+                });
+            }
+        }
+        edges
+    }
+
+    fn area(path: &Path) -> f64 {
+        edges(path)
+            .iter()
+            .map(|edge| edge.from.to_vector().cross(edge.to.to_vector()))
+            .sum::<f64>()
+            .abs()
+            / 2.0
+    }
+
+    fn winding(path: &Path, p: Point2D<f64>) -> i32 {
+        edges(path)
+            .iter()
+            .map(|edge| {
+                let side = (edge.to - edge.from).cross(p - edge.from);
+                if edge.from.y <= p.y && edge.to.y > p.y && side > 0.0 {
+                    1
+                } else if edge.to.y <= p.y && edge.from.y > p.y && side < 0.0 {
+                    -1
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+
+    /// Checks whether `p` is filled by the result of a boolean operation,
+    /// which must be the same under either fill rule.
+    fn fills(path: &Path, x: f64, y: f64) -> bool {
+        let winding = winding(path, point2(x, y));
+        assert_eq!(
+            winding != 0,
+            winding % 2 != 0,
+            "winding {winding} at ({x}, {y})"
+        );
+        winding != 0
+    }
+
+    fn assert_area(path: &Path, expected: f64) {
+        let area = area(path);
+        assert!(
+            (area - expected).abs() < 1e-6,
+            "area {area}, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        for path in [Path::new(), sample_path()] {
+            let bytes = path.to_bytes();
+            assert_eq!(bytes.len() % 8, 0);
+            assert_eq!(Path::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn svg_round_trip() {
+        let path = sample_path();
+        for relative in [false, true] {
+            let svg = path.to_svg(None, relative);
+            assert_eq!(Path::from_svg(&svg).to_svg(None, relative), svg);
+            assert_eq!(
+                Path::from_svg(&svg).to_svg(None, false),
+                path.to_svg(None, false),
+            );
+        }
+    }
+
+    #[test]
+    fn from_bytes_rejects_truncated_input() {
+        let bytes = sample_path().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(
+                matches!(
+                    Path::from_bytes(&bytes[..len]),
+                    Err(Canvas2DError::DecodePath)
+                ),
+                "decoded {len} of {} bytes",
+                bytes.len(),
+            );
+        }
+    }
+
+    #[test]
+    fn from_bytes_rejects_malformed_input() {
+        let bytes = sample_path().to_bytes();
+        let count = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+        let coords_start = (4 + count).next_multiple_of(8);
+        let mut inputs = Vec::new();
+        // Operation counts past the end of the buffer.
+        for count in [count as u32 + 1, u32::MAX] {
+            let mut input = bytes.clone();
+            input[..4].copy_from_slice(&count.to_le_bytes());
+            inputs.push(input);
+        }
+        // Trailing coordinates, and bytes that do not make up a coordinate.
+        for extra in [8, 3] {
+            let mut input = bytes.clone();
+            input.resize(bytes.len() + extra, 0);
+            inputs.push(input);
+        }
+        // Non-finite coordinates.
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let mut input = bytes.clone();
+            input[coords_start..coords_start + 8].copy_from_slice(&value.to_le_bytes());
+            inputs.push(input);
+        }
+        for input in inputs {
+            assert!(matches!(
+                Path::from_bytes(&input),
+                Err(Canvas2DError::DecodePath),
+            ));
+        }
+    }
+
+    #[test]
+    fn from_bytes_rejects_unknown_operations() {
+        let bytes = sample_path().to_bytes();
+        for verb in [5, 0x80, u8::MAX] {
+            let mut input = bytes.clone();
+            input[4] = verb;
+            assert!(matches!(
+                Path::from_bytes(&input),
+                Err(Canvas2DError::DecodePath),
+            ));
+        }
+    }
+
+    #[test]
+    fn boolean_op_overlapping_rectangles() {
+        use PathBooleanOperation::*;
+
+        let a = rect(0.0, 0.0, 10.0, 10.0);
+        let b = rect(5.0, 5.0, 10.0, 10.0);
+        let points = [(2.0, 2.0), (7.0, 7.0), (12.0, 12.0), (12.0, 2.0)];
+        for (operation, expected_area, expected_fills) in [
+            (Union, 175.0, [true, true, true, false]),
+            (Intersection, 25.0, [false, true, false, false]),
+            (Difference, 75.0, [true, false, false, false]),
+            (Xor, 150.0, [true, false, true, false]),
+        ] {
+            for fill_rule in [CanvasFillRule::NonZero, CanvasFillRule::EvenOdd] {
+                let result = a.boolean_op(fill_rule, &b, fill_rule, operation);
+                assert_area(&result, expected_area);
+                for ((x, y), expected) in points.into_iter().zip(expected_fills) {
+                    assert_eq!(
+                        fills(&result, x, y),
+                        expected,
+                        "{operation:?} at ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn boolean_op_shared_edges() {
+        use PathBooleanOperation::*;
+
+        let a = rect(0.0, 0.0, 10.0, 10.0);
+        let beside = rect(10.0, 0.0, 10.0, 10.0);
+        for (operation, expected_area) in [
+            (Union, 200.0),
+            (Intersection, 0.0),
+            (Difference, 100.0),
+            (Xor, 200.0),
+        ] {
+            let result = a.boolean_op(
+                CanvasFillRule::NonZero,
+                &beside,
+                CanvasFillRule::NonZero,
+                operation,
+            );
+            assert_area(&result, expected_area);
+            assert_eq!(
+                fills(&result, 9.0, 5.0),
+                !matches!(operation, Intersection),
+                "{operation:?}"
+            );
+            assert_eq!(
+                fills(&result, 11.0, 5.0),
+                matches!(operation, Union | Xor),
+                "{operation:?}"
+            );
+        }
+
+        let inside = rect(0.0, 0.0, 10.0, 5.0);
+        for (operation, expected_area) in [
+            (Union, 100.0),
+            (Intersection, 50.0),
+            (Difference, 50.0),
+            (Xor, 50.0),
+        ] {
+            let result = a.boolean_op(
+                CanvasFillRule::NonZero,
+                &inside,
+                CanvasFillRule::NonZero,
+                operation,
+            );
+            assert_area(&result, expected_area);
+        }
+
+        for (operation, expected_area) in [
+            (Union, 100.0),
+            (Intersection, 100.0),
+            (Difference, 0.0),
+            (Xor, 0.0),
+        ] {
+            let result = a.boolean_op(
+                CanvasFillRule::NonZero,
+                &a,
+                CanvasFillRule::NonZero,
+                operation,
+            );
+            assert_area(&result, expected_area);
+        }
+    }
+
+    #[test]
+    fn boolean_op_nested_contours() {
+        let mut same_direction = rect(0.0, 0.0, 30.0, 30.0);
+        same_direction.rect(10.0, 10.0, 10.0, 10.0);
+        let mut opposite_direction = rect(0.0, 0.0, 30.0, 30.0);
+        opposite_direction.move_to(10.0, 10.0);
+        opposite_direction.line_to(10.0, 20.0);
+        opposite_direction.line_to(20.0, 20.0);
+        opposite_direction.line_to(20.0, 10.0);
+        opposite_direction.close();
+        let other = rect(25.0, 0.0, 10.0, 30.0);
+        for (path, fill_rule, has_hole) in [
+            (&same_direction, CanvasFillRule::NonZero, false),
+            (&same_direction, CanvasFillRule::EvenOdd, true),
+            (&opposite_direction, CanvasFillRule::NonZero, true),
+            (&opposite_direction, CanvasFillRule::EvenOdd, true),
+        ] {
+            let result = path.boolean_op(
+                fill_rule,
+                &other,
+                CanvasFillRule::NonZero,
+                PathBooleanOperation::Union,
+            );
+            assert_area(&result, if has_hole { 950.0 } else { 1050.0 });
+            assert_eq!(fills(&result, 15.0, 15.0), !has_hole, "{fill_rule:?}");
+            assert!(fills(&result, 5.0, 15.0));
+            assert!(fills(&result, 32.0, 15.0));
+        }
+    }
+
+    #[test]
+    fn boolean_op_empty_operands() {
+        use PathBooleanOperation::*;
+
+        let a = rect(0.0, 0.0, 10.0, 10.0);
+        let empty = Path::new();
+        for (operation, a_and_empty, empty_and_a) in [
+            (Union, 100.0, 100.0),
+            (Intersection, 0.0, 0.0),
+            (Difference, 100.0, 0.0),
+            (Xor, 100.0, 100.0),
+        ] {
+            let rule = CanvasFillRule::NonZero;
+            assert_area(&a.boolean_op(rule, &empty, rule, operation), a_and_empty);
+            assert_area(&empty.boolean_op(rule, &a, rule, operation), empty_and_a);
+            assert_area(&empty.boolean_op(rule, &empty, rule, operation), 0.0);
+        }
+    }
+}
@@ -95,6 +95,48 @@ impl OwnedSource {
         }
     }
 
+    /// Moves the source into the space that `mat` maps its current space to.
+    pub fn transformed(self, mat: &Transform) -> Option<Self> {
+        let inverse = mat.inverse()?;
+        Some(match self {
+            Self::Solid(color) => Self::Solid(color),
+            Self::Image(image, extend, filter, transform, extend_x, extend_y) => Self::Image(
+                image,
+                extend,
+                filter,
+                inverse.then(&transform),
+                extend_x,
+                extend_y,
+            ),
+            Self::RadialGradient(gradient, spread, transform) => {
+                Self::RadialGradient(gradient, spread, inverse.then(&transform))
+            }
+            Self::TwoCircleRadialGradient(gradient, spread, c1, r1, c2, r2, transform) => {
+                Self::TwoCircleRadialGradient(
+                    gradient,
+                    spread,
+                    c1,
+                    r1,
+                    c2,
+                    r2,
+                    inverse.then(&transform),
+                )
+            }
+            Self::LinearGradient(gradient, spread, transform) => {
+                Self::LinearGradient(gradient, spread, inverse.then(&transform))
+            }
+            Self::SweepGradient(gradient, spread, start_angle, end_angle, transform) => {
+                Self::SweepGradient(
+                    gradient,
+                    spread,
+                    start_angle,
+                    end_angle,
+                    inverse.then(&transform),
+                )
+            }
+        })
+    }
+
     pub fn borrow(&self) -> Source<'_> {
         match *self {
             Self::Solid(color) => Source::Solid(color),
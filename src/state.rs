@@ -22,20 +22,26 @@ use super::convert::{
 use super::css::FromCss as _;
 use super::css::color::{AbsoluteColor, ComputedColor};
 use super::css::filter::ComputedFilter;
+use super::css::font::font_face::{SpecifiedFontFeatureSettings, SpecifiedFontVariationSettings};
 use super::css::font::{
     ComputedFamilyName, ComputedFont, ComputedFontFamily, ComputedFontSize,
-    ComputedFontStretchCss3, ComputedFontStyle, ComputedFontVariantCaps, ComputedFontWeight,
+    ComputedFontStretchCss3, ComputedFontStyle, ComputedFontVariantCaps,
+    ComputedFontVariantLigatures, ComputedFontVariantNumeric, ComputedFontWeight,
     ComputedGenericFamily, ComputedLineHeight,
 };
 use super::css::length::{ComputedLength, SpecifiedAbsoluteLength};
 use super::error::Canvas2DError;
 use super::filter::{BoxedRenderFunction, FilterChain, compile_filter};
+use super::glyph_mask;
 use super::gradient::CanvasGradient;
 use super::image_bitmap::ImageBitmap;
 use super::image_data::{AlignedImageDataView, AlignedImageDataViewMut};
 use super::path::{CanvasFillRule, Path};
 use super::pattern::CanvasPattern;
-use super::text::{FontFaceSet, TextMetrics, prepare_text};
+use super::text::{
+    FontFaceSet, MeasuredText, TextLayout, TextLayoutAlign, TextLayoutOptions, prepare_color_text,
+    prepare_text, prepare_text_glyphs, prepare_text_on_path,
+};
 use super::wrap::Wrap;
 use super::{
     ARGB32_ALPHA_MASK, CanvasColorSpace, PredefinedColorSpace, raqote_ext,
@@ -121,7 +127,7 @@ pub enum CanvasDirection {
     Inherit,
 }
 
-#[derive(Clone, Copy, Debug, FromRepr)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, FromRepr)]
 #[repr(i32)]
 pub enum CanvasFontKerning {
     Auto,
@@ -224,6 +230,13 @@ pub enum CanvasTextRendering {
     GeometricPrecision,
 }
 
+#[derive(Clone, Copy, Debug, FromRepr)]
+#[repr(i32)]
+pub enum CanvasTextPathSide {
+    Left,
+    Right,
+}
+
 #[derive(Clone, Copy, Debug, FromRepr)]
 #[repr(i32)]
 pub enum BlendOrCompositeMode {
@@ -414,11 +427,16 @@ pub struct DrawingState {
     pub font_kerning: CanvasFontKerning,
     pub font_stretch: ComputedFontStretchCss3,
     pub font_variant_caps: ComputedFontVariantCaps,
+    pub font_variant_numeric: ComputedFontVariantNumeric,
+    pub font_variant_ligatures: ComputedFontVariantLigatures,
+    pub font_feature_settings: SpecifiedFontFeatureSettings,
+    pub font_variation_settings: SpecifiedFontVariationSettings,
     pub text_rendering: CanvasTextRendering,
     transformation_matrix: Transform2D<f64>,
     fill_style: FillOrStrokeStyle,
     stroke_style: FillOrStrokeStyle,
     clip_depth: usize,
+    clip_bounds: Option<Box2D<f64>>,
     global_alpha: f64,
     compositing_and_blending_operator: BlendOrCompositeMode,
     image_smoothing_enabled: bool,
@@ -456,11 +474,16 @@ impl Default for DrawingState {
             font_kerning: CanvasFontKerning::Auto,
             font_stretch: ComputedFontStretchCss3::Normal,
             font_variant_caps: ComputedFontVariantCaps::Normal,
+            font_variant_numeric: ComputedFontVariantNumeric::default(),
+            font_variant_ligatures: ComputedFontVariantLigatures::default(),
+            font_feature_settings: SpecifiedFontFeatureSettings::default(),
+            font_variation_settings: SpecifiedFontVariationSettings::default(),
             text_rendering: CanvasTextRendering::Auto,
             transformation_matrix: Transform2D::identity(),
             fill_style: FillOrStrokeStyle::Color(AbsoluteColor::OPAQUE_BLACK),
             stroke_style: FillOrStrokeStyle::Color(AbsoluteColor::OPAQUE_BLACK),
             clip_depth: 0,
+            clip_bounds: None,
             global_alpha: 1.0,
             compositing_and_blending_operator: BlendOrCompositeMode::SourceOver,
             image_smoothing_enabled: true,
@@ -671,6 +694,8 @@ impl CanvasState {
     pub fn set_font(&mut self, value: ComputedFont) {
         self.current_drawing_state.font_style = value.style;
         self.current_drawing_state.font_variant_caps = value.variant.modernize();
+        self.current_drawing_state.font_variant_numeric = ComputedFontVariantNumeric::default();
+        self.current_drawing_state.font_variant_ligatures = ComputedFontVariantLigatures::default();
         self.current_drawing_state.font_weight = value.weight;
         self.current_drawing_state.font_stretch = value.stretch;
         self.current_drawing_state.font_size = value.size;
@@ -741,6 +766,38 @@ impl CanvasState {
         self.current_drawing_state.font_variant_caps = value.into();
     }
 
+    pub fn font_variant_numeric(&self) -> ComputedFontVariantNumeric {
+        self.current_drawing_state.font_variant_numeric
+    }
+
+    pub fn set_font_variant_numeric(&mut self, value: ComputedFontVariantNumeric) {
+        self.current_drawing_state.font_variant_numeric = value;
+    }
+
+    pub fn font_variant_ligatures(&self) -> ComputedFontVariantLigatures {
+        self.current_drawing_state.font_variant_ligatures
+    }
+
+    pub fn set_font_variant_ligatures(&mut self, value: ComputedFontVariantLigatures) {
+        self.current_drawing_state.font_variant_ligatures = value;
+    }
+
+    pub fn font_feature_settings(&self) -> SpecifiedFontFeatureSettings {
+        self.current_drawing_state.font_feature_settings.clone()
+    }
+
+    pub fn set_font_feature_settings(&mut self, value: SpecifiedFontFeatureSettings) {
+        self.current_drawing_state.font_feature_settings = value;
+    }
+
+    pub fn font_variation_settings(&self) -> SpecifiedFontVariationSettings {
+        self.current_drawing_state.font_variation_settings.clone()
+    }
+
+    pub fn set_font_variation_settings(&mut self, value: SpecifiedFontVariationSettings) {
+        self.current_drawing_state.font_variation_settings = value;
+    }
+
     pub fn text_rendering(&self) -> CanvasTextRendering {
         self.current_drawing_state.text_rendering
     }
@@ -788,6 +845,10 @@ impl CanvasState {
         self.current_drawing_state.transformation_matrix
     }
 
+    pub fn inverse_transform(&self) -> Option<Transform2D<f64>> {
+        self.current_drawing_state.transformation_matrix.inverse()
+    }
+
     pub fn set_transform(&mut self, mat: Transform2D<f64>) {
         self.current_drawing_state.transformation_matrix = mat;
         self.update_transform();
@@ -1043,20 +1104,106 @@ impl CanvasState {
         });
     }
 
+    pub fn text_to_path(
+        &self,
+        fonts: &FontFaceSet,
+        text: &str,
+        x: f64,
+        y: f64,
+        max_width: f64,
+    ) -> Path {
+        let (path, _) = prepare_text(fonts, &self.current_drawing_state, text, max_width as f32);
+        path.transform(&Transform2D::new(1.0, 0.0, 0.0, -1.0, x, y))
+    }
+
     pub fn fill_text(&mut self, fonts: &FontFaceSet, text: &str, x: f64, y: f64, max_width: f64) {
         let color_space = self.color_space;
+        // Colour glyphs that use the text colour can only take a solid one.
+        let foreground = match self.current_drawing_state.fill_style {
+            FillOrStrokeStyle::Color(color) => color,
+            _ => AbsoluteColor::OPAQUE_BLACK,
+        };
+        if glyph_mask::is_translation(self.draw_target.get_transform())
+            && self.current_drawing_state.font_size.0.px <= glyph_mask::MAX_FONT_SIZE
+        {
+            let glyphs = prepare_text_glyphs(
+                fonts,
+                &self.current_drawing_state,
+                text,
+                max_width as f32,
+                foreground,
+            );
+            if let Some(glyphs) = glyphs {
+                let glyph_masks = fonts.glyph_masks();
+                self.paint(move |this| {
+                    let source = this
+                        .current_drawing_state
+                        .get_raqote_fill_source(color_space);
+                    move |draw_target, draw_options| {
+                        let Some(ref source) = source else {
+                            return;
+                        };
+                        // Filters may render with a transform of their own.
+                        let transform = *draw_target.get_transform();
+                        if glyph_mask::is_translation(&transform) {
+                            let origin = point2(x, y) + vec2(transform.m31, transform.m32).cast();
+                            glyph_masks.borrow_mut().draw(
+                                draw_target,
+                                &glyphs,
+                                origin,
+                                &source.borrow(),
+                                &draw_options,
+                            );
+                        } else {
+                            let path = glyph_mask::glyphs_to_path(&glyphs, point2(x, y))
+                                .to_raqote(CanvasFillRule::NonZero);
+                            draw_target.fill(&path, &source.borrow(), &draw_options);
+                        }
+                    }
+                });
+                return;
+            }
+        }
         self.paint(move |this| {
-            let (path, _) =
-                prepare_text(fonts, &this.current_drawing_state, text, max_width as f32);
-            let path = path.transform(&Transform2D::new(1.0, 0.0, 0.0, -1.0, x, y));
-            let path = path.to_raqote(CanvasFillRule::NonZero);
-            let source = this
-                .current_drawing_state
-                .get_raqote_fill_source(color_space);
+            let drawing_state = &this.current_drawing_state;
+            let (path, layers) =
+                prepare_color_text(fonts, drawing_state, text, max_width as f32, foreground);
+            let transform = Transform2D::new(1.0, 0.0, 0.0, -1.0, x, y);
+            let path = path
+                .transform(&transform)
+                .to_raqote(CanvasFillRule::NonZero);
+            let source = drawing_state.get_raqote_fill_source(color_space);
+            let layers = layers
+                .into_iter()
+                .filter_map(|layer| {
+                    let layer = layer.transform(&transform);
+                    let source =
+                        layer.to_raqote(color_space, drawing_state.image_smoothing_enabled)?;
+                    Some((layer.path.to_raqote(CanvasFillRule::NonZero), source))
+                })
+                .collect::<Vec<_>>();
             move |draw_target, draw_options| {
+                if layers.is_empty() {
+                    if let Some(ref source) = source {
+                        draw_target.fill(&path, &source.borrow(), &draw_options);
+                    }
+                    return;
+                }
+                // Composite the glyphs as a whole, so that overlapping layers
+                // do not show through each other.
+                draw_target.push_layer_with_blend(draw_options.alpha, draw_options.blend_mode);
+                let layer_options = raqote::DrawOptions {
+                    blend_mode: raqote::BlendMode::SrcOver,
+                    alpha: 1.0,
+                    ..draw_options
+                };
                 if let Some(ref source) = source {
-                    draw_target.fill(&path, &source.borrow(), &draw_options);
+                    draw_target.fill(&path, &source.borrow(), &layer_options);
+                }
+                for (path, source) in &layers {
+                    draw_target.fill(path, &source.borrow(), &layer_options);
                 }
+                draw_target.pop_layer();
             }
         });
     }
@@ -1064,9 +1211,7 @@ impl CanvasState {
     pub fn stroke_text(&mut self, fonts: &FontFaceSet, text: &str, x: f64, y: f64, max_width: f64) {
         let color_space = self.color_space;
         self.paint(move |this| {
-            let (path, _) =
-                prepare_text(fonts, &this.current_drawing_state, text, max_width as f32);
-            let path = path.transform(&Transform2D::new(1.0, 0.0, 0.0, -1.0, x, y));
+            let path = this.text_to_path(fonts, text, x, y, max_width);
             let path = this.draw_target.trace_path(
                 &raqote::Path {
                     ops: path.to_raqote_ops(),
@@ -1085,10 +1230,79 @@ impl CanvasState {
         });
     }
 
-    pub fn measure_text(&self, fonts: &FontFaceSet, text: &str) -> TextMetrics {
-        let (_, text_metrics) =
-            prepare_text(fonts, &self.current_drawing_state, text, f32::INFINITY);
-        text_metrics
+    pub fn text_on_path_to_path(
+        &self,
+        fonts: &FontFaceSet,
+        text: &str,
+        path: &Path,
+        start_offset: f64,
+        side: CanvasTextPathSide,
+        max_width: f64,
+    ) -> Path {
+        prepare_text_on_path(
+            fonts,
+            &self.current_drawing_state,
+            text,
+            path,
+            start_offset,
+            side,
+            max_width as f32,
+        )
+    }
+
+    pub fn fill_text_on_path(
+        &mut self,
+        fonts: &FontFaceSet,
+        text: &str,
+        path: &Path,
+        start_offset: f64,
+        side: CanvasTextPathSide,
+        max_width: f64,
+    ) {
+        let path = self.text_on_path_to_path(fonts, text, path, start_offset, side, max_width);
+        self.fill(&path, CanvasFillRule::NonZero);
+    }
+
+    pub fn stroke_text_on_path(
+        &mut self,
+        fonts: &FontFaceSet,
+        text: &str,
+        path: &Path,
+        start_offset: f64,
+        side: CanvasTextPathSide,
+        max_width: f64,
+    ) {
+        let path = self.text_on_path_to_path(fonts, text, path, start_offset, side, max_width);
+        self.stroke(&path);
+    }
+
+    pub fn layout_text(
+        &self,
+        fonts: &FontFaceSet,
+        text: &str,
+        options: &TextLayoutOptions,
+    ) -> TextLayout {
+        TextLayout::new(fonts, &self.current_drawing_state, text, options)
+    }
+
+    pub fn fill_text_layout(&mut self, layout: &TextLayout, x: f64, y: f64) {
+        let path = layout
+            .path()
+            .clone()
+            .transform(&Transform2D::translation(x, y));
+        self.fill(&path, CanvasFillRule::NonZero);
+    }
+
+    pub fn stroke_text_layout(&mut self, layout: &TextLayout, x: f64, y: f64) {
+        let path = layout
+            .path()
+            .clone()
+            .transform(&Transform2D::translation(x, y));
+        self.stroke(&path);
+    }
+
+    pub fn measure_text(&self, fonts: &FontFaceSet, text: &str) -> MeasuredText {
+        MeasuredText::new(fonts, &self.current_drawing_state, text)
     }
 
     pub fn fill(&mut self, path: &Path, fill_rule: CanvasFillRule) {
@@ -1127,12 +1341,61 @@ impl CanvasState {
         });
     }
 
+    pub fn stroke_to_path(&self, path: &Path, style: &raqote::StrokeStyle) -> Path {
+        let path = self.draw_target.trace_path(
+            &raqote::Path {
+                ops: path.to_raqote_ops(),
+                winding: raqote::Winding::NonZero,
+            },
+            style,
+        );
+        Path::from_raqote(&path)
+    }
+
     pub fn clip(&mut self, path: &Path, fill_rule: CanvasFillRule) {
+        let bounds = path
+            .clone()
+            .transform(&self.current_drawing_state.transformation_matrix)
+            .control_bounds()
+            .unwrap_or_else(Box2D::zero);
+        let clip_bounds = &mut self.current_drawing_state.clip_bounds;
+        *clip_bounds = Some(match *clip_bounds {
+            Some(clip_bounds) => clip_bounds.intersection_unchecked(&bounds),
+            None => bounds,
+        });
         let path = path.to_raqote(fill_rule);
         self.draw_target.push_clip(&path);
         self.current_drawing_state.clip_depth += 1;
     }
 
+    pub fn device_clip_bounds(&self) -> Box2D<f64> {
+        let canvas_bounds = Box2D::from_size(size2(self.width() as f64, self.height() as f64));
+        match self.current_drawing_state.clip_bounds {
+            Some(clip_bounds) => canvas_bounds
+                .intersection(&clip_bounds)
+                .unwrap_or_else(Box2D::zero),
+            None => canvas_bounds,
+        }
+    }
+
+    pub fn local_clip_bounds(&self) -> Option<Box2D<f64>> {
+        let device_bounds = self.device_clip_bounds();
+        if device_bounds.is_empty() {
+            return Some(Box2D::zero());
+        }
+        self.inverse_transform()
+            .map(|mat| mat.outer_transformed_box(&device_bounds))
+    }
+
+    pub fn is_rect_clipped_out(&self, x: f64, y: f64, w: f64, h: f64) -> bool {
+        let rect = Box2D::from_points([point2(x, y), point2(x + w, y + h)]);
+        let rect = self
+            .current_drawing_state
+            .transformation_matrix
+            .outer_transformed_box(&rect);
+        !self.device_clip_bounds().intersects(&rect)
+    }
+
     pub fn is_point_in_path(&self, path: &Path, x: f64, y: f64, fill_rule: CanvasFillRule) -> bool {
         let path = path.to_raqote(fill_rule);
         path.transform(self.draw_target.get_transform())
@@ -1713,6 +1976,98 @@ pub fn op_canvas_2d_state_set_font_variant_caps(
     this.set_font_variant_caps(value)
 }
 
+#[op2]
+#[string]
+pub fn op_canvas_2d_state_font_variant_numeric(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+) -> String {
+    let this = this.borrow();
+    this.font_variant_numeric().to_css_string()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_set_font_variant_numeric(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[string] value: &str,
+) -> bool {
+    let mut this = this.borrow_mut();
+    if let Ok(value) = ComputedFontVariantNumeric::from_css_string(value) {
+        this.set_font_variant_numeric(value);
+        true
+    } else {
+        false
+    }
+}
+
+#[op2]
+#[string]
+pub fn op_canvas_2d_state_font_variant_ligatures(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+) -> String {
+    let this = this.borrow();
+    this.font_variant_ligatures().to_css_string()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_set_font_variant_ligatures(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[string] value: &str,
+) -> bool {
+    let mut this = this.borrow_mut();
+    if let Ok(value) = ComputedFontVariantLigatures::from_css_string(value) {
+        this.set_font_variant_ligatures(value);
+        true
+    } else {
+        false
+    }
+}
+
+#[op2]
+#[string]
+pub fn op_canvas_2d_state_font_feature_settings(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+) -> String {
+    let this = this.borrow();
+    this.font_feature_settings().to_css_string()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_set_font_feature_settings(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[string] value: &str,
+) -> bool {
+    let mut this = this.borrow_mut();
+    if let Ok(value) = SpecifiedFontFeatureSettings::from_css_string(value) {
+        this.set_font_feature_settings(value);
+        true
+    } else {
+        false
+    }
+}
+
+#[op2]
+#[string]
+pub fn op_canvas_2d_state_font_variation_settings(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+) -> String {
+    let this = this.borrow();
+    this.font_variation_settings().to_css_string()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_set_font_variation_settings(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[string] value: &str,
+) -> bool {
+    let mut this = this.borrow_mut();
+    if let Ok(value) = SpecifiedFontVariationSettings::from_css_string(value) {
+        this.set_font_variation_settings(value);
+        true
+    } else {
+        false
+    }
+}
+
 #[op2(fast)]
 pub fn op_canvas_2d_state_text_rendering(#[cppgc] this: &Wrap<RefCell<CanvasState>>) -> i32 {
     let this = this.borrow();
@@ -1778,6 +2133,18 @@ pub fn op_canvas_2d_state_get_transform(
     out.copy_from_slice(&this.get_transform().to_array())
 }
 
+#[op2(fast)]
+pub fn op_canvas_2d_state_get_inverse_transform(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[buffer] out: &mut [f64],
+) {
+    let this = this.borrow();
+    match this.inverse_transform() {
+        Some(mat) => out.copy_from_slice(&mat.to_array()),
+        None => out.fill(f64::NAN),
+    }
+}
+
 #[op2(fast)]
 pub fn op_canvas_2d_state_set_transform(
     #[cppgc] this: &Wrap<RefCell<CanvasState>>,
@@ -1963,16 +2330,126 @@ pub fn op_canvas_2d_state_stroke_text(
 }
 
 #[op2(fast)]
+pub fn op_canvas_2d_state_fill_text_on_path(
+    state: &OpState,
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[string] text: &str,
+    #[cppgc] path: &Wrap<RefCell<Path>>,
+    start_offset: f64,
+    side: i32,
+    max_width: f64,
+) {
+    let mut this = this.borrow_mut();
+    let path = path.borrow();
+    let side = CanvasTextPathSide::from_repr(side).unwrap();
+    if start_offset.is_finite() {
+        let fonts = state.borrow::<Rc<RefCell<FontFaceSet>>>().borrow();
+        this.fill_text_on_path(&fonts, text, &path, start_offset, side, max_width);
+    }
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_stroke_text_on_path(
+    state: &OpState,
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[string] text: &str,
+    #[cppgc] path: &Wrap<RefCell<Path>>,
+    start_offset: f64,
+    side: i32,
+    max_width: f64,
+) {
+    let mut this = this.borrow_mut();
+    let path = path.borrow();
+    let side = CanvasTextPathSide::from_repr(side).unwrap();
+    if start_offset.is_finite() {
+        let fonts = state.borrow::<Rc<RefCell<FontFaceSet>>>().borrow();
+        this.stroke_text_on_path(&fonts, text, &path, start_offset, side, max_width);
+    }
+}
+
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_state_text_to_path(
+    state: &OpState,
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[string] text: &str,
+    x: f64,
+    y: f64,
+    max_width: f64,
+) -> Wrap<RefCell<Path>> {
+    let this = this.borrow();
+    let path = if [x, y].into_iter().all(f64::is_finite) {
+        let fonts = state.borrow::<Rc<RefCell<FontFaceSet>>>().borrow();
+        this.text_to_path(&fonts, text, x, y, max_width)
+    } else {
+        Path::new()
+    };
+    Wrap::new(RefCell::new(path))
+}
+
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_state_layout_text(
+    state: &OpState,
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[string] text: &str,
+    max_width: f64,
+    line_height: f64,
+    align: i32,
+    max_lines: u32,
+    #[string] ellipsis: String,
+) -> Wrap<TextLayout> {
+    let this = this.borrow();
+    let fonts = state.borrow::<Rc<RefCell<FontFaceSet>>>().borrow();
+    let options = TextLayoutOptions {
+        max_width: max_width as f32,
+        line_height: line_height.is_finite().then_some(line_height as f32),
+        align: TextLayoutAlign::from_repr(align).unwrap(),
+        max_lines: (max_lines != 0).then_some(max_lines as usize),
+        ellipsis,
+    };
+    Wrap::new(this.layout_text(&fonts, text, &options))
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_fill_text_layout(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[cppgc] layout: &Wrap<TextLayout>,
+    x: f64,
+    y: f64,
+) {
+    let mut this = this.borrow_mut();
+    if [x, y].into_iter().all(f64::is_finite) {
+        this.fill_text_layout(layout, x, y);
+    }
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_stroke_text_layout(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[cppgc] layout: &Wrap<TextLayout>,
+    x: f64,
+    y: f64,
+) {
+    let mut this = this.borrow_mut();
+    if [x, y].into_iter().all(f64::is_finite) {
+        this.stroke_text_layout(layout, x, y);
+    }
+}
+
+#[op2]
+#[cppgc]
 pub fn op_canvas_2d_state_measure_text(
     state: &OpState,
     #[cppgc] this: &Wrap<RefCell<CanvasState>>,
     #[string] text: &str,
     #[buffer] out: &mut [f64],
-) {
+) -> Wrap<MeasuredText> {
     let this = this.borrow();
-    let out = &mut out[..12];
+    let out = &mut out[..15];
     let fonts = state.borrow::<Rc<RefCell<FontFaceSet>>>().borrow();
-    let result = this.measure_text(&fonts, text);
+    let measured = this.measure_text(&fonts, text);
+    let result = measured.metrics();
     out[0] = result.width as f64;
     out[1] = result.actual_bounding_box_left as f64;
     out[2] = result.actual_bounding_box_right as f64;
@@ -1985,6 +2462,10 @@ pub fn op_canvas_2d_state_measure_text(
     out[9] = result.hanging_baseline as f64;
     out[10] = result.alphabetic_baseline as f64;
     out[11] = result.ideographic_baseline as f64;
+    out[12] = result.font_line_gap as f64;
+    out[13] = result.cap_height as f64;
+    out[14] = result.x_height as f64;
+    Wrap::new(measured)
 }
 
 #[op2(fast)]
@@ -2009,6 +2490,31 @@ pub fn op_canvas_2d_state_stroke(
     this.stroke(&path)
 }
 
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_state_stroke_to_path(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[cppgc] path: &Wrap<RefCell<Path>>,
+    line_width: f64,
+    line_cap: i32,
+    line_join: i32,
+    miter_limit: f64,
+    #[buffer] dash_list: &[f64],
+    dash_offset: f64,
+) -> Wrap<RefCell<Path>> {
+    let this = this.borrow();
+    let path = path.borrow();
+    let style = raqote::StrokeStyle {
+        width: line_width as f32,
+        cap: CanvasLineCap::from_repr(line_cap).unwrap().to_raqote(),
+        join: CanvasLineJoin::from_repr(line_join).unwrap().to_raqote(),
+        miter_limit: miter_limit as f32,
+        dash_array: dash_list.iter().map(|&x| x as f32).collect(),
+        dash_offset: dash_offset as f32,
+    };
+    Wrap::new(RefCell::new(this.stroke_to_path(&path, &style)))
+}
+
 #[op2(fast)]
 pub fn op_canvas_2d_state_clip(
     #[cppgc] this: &Wrap<RefCell<CanvasState>>,
@@ -2021,6 +2527,43 @@ pub fn op_canvas_2d_state_clip(
     this.clip(&path, fill_rule)
 }
 
+#[op2(fast)]
+pub fn op_canvas_2d_state_get_device_clip_bounds(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[buffer] out: &mut [f64],
+) {
+    let this = this.borrow();
+    let bounds = this.device_clip_bounds();
+    out.copy_from_slice(&[bounds.min.x, bounds.min.y, bounds.width(), bounds.height()])
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_get_local_clip_bounds(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    #[buffer] out: &mut [f64],
+) -> bool {
+    let this = this.borrow();
+    match this.local_clip_bounds() {
+        Some(bounds) => {
+            out.copy_from_slice(&[bounds.min.x, bounds.min.y, bounds.width(), bounds.height()]);
+            true
+        }
+        None => false,
+    }
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_state_is_rect_clipped_out(
+    #[cppgc] this: &Wrap<RefCell<CanvasState>>,
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+) -> bool {
+    let this = this.borrow();
+    ![x, y, w, h].into_iter().all(f64::is_finite) || this.is_rect_clipped_out(x, y, w, h)
+}
+
 #[op2(fast)]
 pub fn op_canvas_2d_state_is_point_in_path(
     #[cppgc] this: &Wrap<RefCell<CanvasState>>,
@@ -0,0 +1,484 @@
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use harfbuzz_rs as hb;
+use hashlink::LruCache;
+use unicase::UniCase;
+
+use super::css::angle::SpecifiedAngle;
+use super::css::font::font_face::{
+    SpecifiedFontStyleRange, SpecifiedFontWeightRange, SpecifiedFontWidthRange,
+    SpecifiedUnicodeRange,
+};
+use super::css::font::{ComputedFontStyle, ComputedGenericFamily};
+use super::css::font::{SpecifiedAbsoluteFontWeight, SpecifiedFontWidth, SpecifiedSpecificFamily};
+use super::text::{FontAttributes, FontFace, FontFaceData, FontFaceState};
+
+/// Where to look for fonts installed on the system.
+#[derive(Clone, Debug)]
+pub struct SystemFontOptions {
+    /// Scan the platform's standard font locations (the directories listed in
+    /// the fontconfig configuration on Linux).
+    pub load_system_fonts: bool,
+    /// Additional directories to scan recursively.
+    pub font_dirs: Vec<PathBuf>,
+}
+
+impl Default for SystemFontOptions {
+    fn default() -> Self {
+        Self {
+            load_system_fonts: true,
+            font_dirs: Vec::new(),
+        }
+    }
+}
+
+/// The concrete families each generic family (`serif`, `monospace`, ...) stands
+/// for, in order of preference. Names are resolved against the fonts added to
+/// the document's `FontFaceSet` first, then against installed fonts.
+#[derive(Clone, Debug)]
+pub struct GenericFontFamilies {
+    families: HashMap<ComputedGenericFamily, Vec<String>>,
+}
+
+impl GenericFontFamilies {
+    /// A table that maps every generic family to nothing.
+    pub fn empty() -> Self {
+        Self {
+            families: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, generic: ComputedGenericFamily) -> &[String] {
+        self.families.get(&generic).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn set<I>(&mut self, generic: ComputedGenericFamily, families: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.families
+            .insert(generic, families.into_iter().map(Into::into).collect());
+    }
+}
+
+impl Default for GenericFontFamilies {
+    /// Common families shipped with Linux distributions, macOS and Windows.
+    fn default() -> Self {
+        const SERIF: &[&str] = &[
+            "DejaVu Serif",
+            "Noto Serif",
+            "Liberation Serif",
+            "Times New Roman",
+            "Times",
+        ];
+        const SANS_SERIF: &[&str] = &[
+            "DejaVu Sans",
+            "Noto Sans",
+            "Liberation Sans",
+            "Arial",
+            "Helvetica",
+        ];
+        const MONOSPACE: &[&str] = &[
+            "DejaVu Sans Mono",
+            "Noto Sans Mono",
+            "Liberation Mono",
+            "Courier New",
+            "Courier",
+        ];
+        const SYSTEM_UI: &[&str] = &[
+            "Cantarell",
+            "Ubuntu",
+            "Segoe UI",
+            "SF Pro Text",
+            "Noto Sans",
+            "DejaVu Sans",
+        ];
+        let mut this = Self::empty();
+        this.set(ComputedGenericFamily::Serif, SERIF.iter().copied());
+        this.set(ComputedGenericFamily::SansSerif, SANS_SERIF.iter().copied());
+        this.set(
+            ComputedGenericFamily::Cursive,
+            ["Comic Sans MS", "Apple Chancery", "URW Chancery L"],
+        );
+        this.set(
+            ComputedGenericFamily::Fantasy,
+            ["Impact", "Papyrus", "URW Bookman"],
+        );
+        this.set(ComputedGenericFamily::Monospace, MONOSPACE.iter().copied());
+        this.set(ComputedGenericFamily::SystemUi, SYSTEM_UI.iter().copied());
+        this.set(
+            ComputedGenericFamily::Emoji,
+            [
+                "Noto Color Emoji",
+                "Apple Color Emoji",
+                "Segoe UI Emoji",
+                "Twemoji Mozilla",
+            ],
+        );
+        this.set(
+            ComputedGenericFamily::Math,
+            [
+                "Noto Sans Math",
+                "STIX Two Math",
+                "Cambria Math",
+                "Latin Modern Math",
+            ],
+        );
+        this.set(ComputedGenericFamily::Fangsong, ["FangSong", "STFangsong"]);
+        this.set(ComputedGenericFamily::Kai, ["KaiTi", "STKaiti"]);
+        this.set(
+            ComputedGenericFamily::Nastaliq,
+            ["Noto Nastaliq Urdu", "Urdu Typesetting"],
+        );
+        this.set(ComputedGenericFamily::UiSerif, SERIF.iter().copied());
+        this.set(
+            ComputedGenericFamily::UiSansSerif,
+            SYSTEM_UI.iter().copied(),
+        );
+        this.set(
+            ComputedGenericFamily::UiMonospace,
+            MONOSPACE.iter().copied(),
+        );
+        this
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SystemFontId(fontdb::ID);
+
+/// The characters that a face maps to glyphs, as sorted, disjoint ranges.
+#[derive(Debug)]
+struct Coverage(Box<[RangeInclusive<u32>]>);
+
+impl Coverage {
+    fn new(face: &ttf_parser::Face) -> Self {
+        let mut codepoints = Vec::new();
+        for subtable in face
+            .tables()
+            .cmap
+            .into_iter()
+            .flat_map(|cmap| cmap.subtables)
+        {
+            if subtable.is_unicode() {
+                subtable.codepoints(|c| codepoints.push(c));
+            }
+        }
+        codepoints.sort_unstable();
+        codepoints.dedup();
+        let mut ranges = Vec::<RangeInclusive<u32>>::new();
+        for c in codepoints {
+            match ranges.last_mut() {
+                Some(range) if *range.end() + 1 == c => *range = *range.start()..=c,
+                _ => ranges.push(c..=c),
+            }
+        }
+        Self(ranges.into())
+    }
+
+    fn contains(&self, c: char) -> bool {
+        let c = c as u32;
+        let index = self.0.partition_point(|range| *range.end() < c);
+        self.0.get(index).is_some_and(|range| range.contains(&c))
+    }
+}
+
+fn font_width(stretch: fontdb::Stretch) -> SpecifiedFontWidth {
+    match stretch {
+        fontdb::Stretch::UltraCondensed => SpecifiedFontWidth::UltraCondensed,
+        fontdb::Stretch::ExtraCondensed => SpecifiedFontWidth::ExtraCondensed,
+        fontdb::Stretch::Condensed => SpecifiedFontWidth::Condensed,
+        fontdb::Stretch::SemiCondensed => SpecifiedFontWidth::SemiCondensed,
+        fontdb::Stretch::Normal => SpecifiedFontWidth::Normal,
+        fontdb::Stretch::SemiExpanded => SpecifiedFontWidth::SemiExpanded,
+        fontdb::Stretch::Expanded => SpecifiedFontWidth::Expanded,
+        fontdb::Stretch::ExtraExpanded => SpecifiedFontWidth::ExtraExpanded,
+        fontdb::Stretch::UltraExpanded => SpecifiedFontWidth::UltraExpanded,
+    }
+}
+
+#[derive(Debug)]
+struct SystemFontIndex {
+    db: fontdb::Database,
+    families: HashMap<String, Vec<fontdb::ID>>,
+    /// Built the first time a character is not covered by any requested
+    /// family, since it means reading every installed font.
+    coverage: OnceCell<Box<[(fontdb::ID, Coverage)]>>,
+    /// The contents of every font file that faces have been loaded from,
+    /// shared by all the faces in the file.
+    blobs: RefCell<HashMap<PathBuf, hb::Shared<hb::Blob<'static>>>>,
+}
+
+impl SystemFontIndex {
+    fn new(options: &SystemFontOptions) -> Self {
+        let mut db = fontdb::Database::new();
+        if options.load_system_fonts {
+            db.load_system_fonts();
+        }
+        for dir in &options.font_dirs {
+            db.load_fonts_dir(dir);
+        }
+        let mut families = HashMap::<_, Vec<_>>::new();
+        for face in db.faces() {
+            for (name, _) in &face.families {
+                let ids = families
+                    .entry(UniCase::new(name.as_str()).to_folded_case())
+                    .or_default();
+                if !ids.contains(&face.id) {
+                    ids.push(face.id);
+                }
+            }
+        }
+        Self {
+            db,
+            families,
+            coverage: OnceCell::new(),
+            blobs: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn coverage(&self) -> &[(fontdb::ID, Coverage)] {
+        self.coverage.get_or_init(|| {
+            self.db
+                .faces()
+                .filter_map(|face| {
+                    let coverage = self
+                        .db
+                        .with_face_data(face.id, |data, index| {
+                            Some(Coverage::new(&ttf_parser::Face::parse(data, index).ok()?))
+                        })
+                        .flatten()?;
+                    Some((face.id, coverage))
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the contents of the file that a face is in, and the index of
+    /// the face in it.
+    fn load_blob(&self, id: fontdb::ID) -> Option<(hb::Shared<hb::Blob<'static>>, u32)> {
+        let (source, index) = self.db.face_source(id)?;
+        let path = match source {
+            fontdb::Source::File(path) | fontdb::Source::SharedFile(path, _) => path,
+            fontdb::Source::Binary(data) => {
+                return Some(((*data).as_ref().to_vec().into(), index));
+            }
+        };
+        if let Some(blob) = self.blobs.borrow().get(&path) {
+            return Some((blob.clone(), index));
+        }
+        let blob = self
+            .db
+            .with_face_data(id, |data, _| hb::Shared::from(data.to_vec()))?;
+        self.blobs.borrow_mut().insert(path, blob.clone());
+        Some((blob, index))
+    }
+
+    fn full_name(&self, id: fontdb::ID) -> Option<String> {
+        self.db
+            .with_face_data(id, |data, index| {
+                let face = ttf_parser::Face::parse(data, index).ok()?;
+                face.names()
+                    .into_iter()
+                    .filter(|name| name.name_id == ttf_parser::name_id::FULL_NAME)
+                    .find_map(|name| name.to_string())
+            })
+            .flatten()
+    }
+
+    fn load_face(&self, id: fontdb::ID) -> Option<Rc<FontFace>> {
+        let info = self.db.face(id)?;
+        let family = info.families.first()?.0.as_str().into();
+        let style = match info.style {
+            fontdb::Style::Normal => SpecifiedFontStyleRange::Normal,
+            fontdb::Style::Italic => SpecifiedFontStyleRange::Italic,
+            fontdb::Style::Oblique => SpecifiedFontStyleRange::Oblique(
+                SpecifiedAngle::Deg(14.0),
+                SpecifiedAngle::Deg(14.0),
+            ),
+        };
+        let weight = SpecifiedAbsoluteFontWeight::Number(info.weight.0 as f32);
+        let width = font_width(info.stretch);
+        let mut weight = SpecifiedFontWeightRange(weight, weight);
+        let mut width = SpecifiedFontWidthRange(width, width);
+        let (blob, index) = self.load_blob(id)?;
+        // Variable fonts cover a range of weights and widths rather than the
+        // single value recorded in their `OS/2` table.
+        if let Ok(face) = ttf_parser::Face::parse(&blob, index) {
+            for axis in face.variation_axes() {
+                match &axis.tag.to_bytes() {
+                    b"wght" => {
+                        weight = SpecifiedFontWeightRange(
+                            SpecifiedAbsoluteFontWeight::Number(axis.min_value),
+                            SpecifiedAbsoluteFontWeight::Number(axis.max_value),
+                        );
+                    }
+                    b"wdth" => {
+                        width = SpecifiedFontWidthRange(
+                            SpecifiedFontWidth::Percentage(axis.min_value / 100.0),
+                            SpecifiedFontWidth::Percentage(axis.max_value / 100.0),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let font = hb::Font::new(hb::Face::new(blob, index));
+        Some(Rc::new(FontFace::new(FontFaceData::new(
+            SpecifiedSpecificFamily { name: family },
+            style,
+            weight,
+            width,
+            SpecifiedUnicodeRange::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            FontFaceState::Loaded(font.into()),
+        ))))
+    }
+}
+
+/// The font used when neither the font set nor the installed fonts have a
+/// glyph for a character. `None` unless the `bundled-fallback-font` feature is
+/// enabled.
+pub fn bundled_fallback_font() -> Option<Rc<FontFace>> {
+    #[cfg(feature = "bundled-fallback-font")]
+    {
+        static TUFFY: &[u8] = include_bytes!("../fonts/Tuffy.ttf");
+        let weight = SpecifiedAbsoluteFontWeight::Number(400.0);
+        let font = hb::Font::new(hb::Face::new(TUFFY, 0));
+        Some(Rc::new(FontFace::new(FontFaceData::new(
+            SpecifiedSpecificFamily {
+                name: "Tuffy".into(),
+            },
+            SpecifiedFontStyleRange::Normal,
+            SpecifiedFontWeightRange(weight, weight),
+            SpecifiedFontWidthRange(SpecifiedFontWidth::Normal, SpecifiedFontWidth::Normal),
+            SpecifiedUnicodeRange::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            FontFaceState::Loaded(font.into()),
+        ))))
+    }
+    #[cfg(not(feature = "bundled-fallback-font"))]
+    None
+}
+
+/// The fonts installed on the system. Directories are scanned the first time a
+/// font is looked up, and the faces of a family are loaded the first time that
+/// family is matched.
+#[derive(Debug)]
+pub struct SystemFontDatabase {
+    options: SystemFontOptions,
+    index: OnceCell<SystemFontIndex>,
+    families: RefCell<HashMap<String, Rc<[Rc<FontFace>]>>>,
+    families_by_char: RefCell<LruCache<(char, bool, u32, u32), Option<Rc<str>>>>,
+}
+
+impl SystemFontDatabase {
+    pub fn new(options: SystemFontOptions) -> Self {
+        Self {
+            options,
+            index: OnceCell::new(),
+            families: RefCell::new(HashMap::new()),
+            families_by_char: RefCell::new(LruCache::new(1024)),
+        }
+    }
+
+    fn index(&self) -> &SystemFontIndex {
+        self.index
+            .get_or_init(|| SystemFontIndex::new(&self.options))
+    }
+
+    pub fn match_family(&self, casefolded_family: &str) -> Rc<[Rc<FontFace>]> {
+        if let Some(faces) = self.families.borrow().get(casefolded_family) {
+            return faces.clone();
+        }
+        let index = self.index();
+        let faces = index
+            .families
+            .get(casefolded_family)
+            .into_iter()
+            .flatten()
+            .filter_map(|&id| index.load_face(id))
+            .collect::<Rc<[_]>>();
+        self.families
+            .borrow_mut()
+            .insert(casefolded_family.to_owned(), faces.clone());
+        faces
+    }
+
+    /// Finds an installed family with a glyph for `c`, for characters that
+    /// none of the requested families cover. Among the faces that have one,
+    /// the closest to `attrs` in width, then style, then weight is chosen.
+    /// Returns the casefolded family name.
+    pub fn find_family_for_char(&self, c: char, attrs: FontAttributes) -> Option<Rc<str>> {
+        let italic = !matches!(attrs.style, ComputedFontStyle::Normal);
+        let key = (c, italic, attrs.weight.0.to_bits(), attrs.width.0.to_bits());
+        if let Some(family) = self.families_by_char.borrow_mut().get(&key) {
+            return family.clone();
+        }
+        let index = self.index();
+        let family = index
+            .coverage()
+            .iter()
+            .filter(|(_, coverage)| coverage.contains(c))
+            .filter_map(|&(id, _)| index.db.face(id))
+            .min_by(|a, b| {
+                let distance = |face: &fontdb::FaceInfo| {
+                    (
+                        (font_width(face.stretch).compute().0 - attrs.width.0).abs(),
+                        italic == (face.style == fontdb::Style::Normal),
+                        (f32::from(face.weight.0) - attrs.weight.0).abs(),
+                    )
+                };
+                distance(a).partial_cmp(&distance(b)).unwrap()
+            })
+            .and_then(|face| face.families.first())
+            .map(|(family, _)| UniCase::new(family.as_str()).to_folded_case().into());
+        self.families_by_char
+            .borrow_mut()
+            .insert(key, family.clone());
+        family
+    }
+
+    /// Looks up a face by its full name or PostScript name, as `local()` does.
+    pub fn find_local(&self, name: &str) -> Option<SystemFontId> {
+        let index = self.index();
+        let name = UniCase::new(name);
+        let casefolded_name = name.to_folded_case();
+        let face = index.db.faces().find(|face| {
+            // Full names almost always start with the family name; checking that
+            // first avoids reading the `name` table of every installed font.
+            UniCase::new(face.post_script_name.as_str()) == name
+                || (face.families.iter().any(|(family, _)| {
+                    casefolded_name.starts_with(&UniCase::new(family.as_str()).to_folded_case())
+                }) && index
+                    .full_name(face.id)
+                    .is_some_and(|full_name| UniCase::new(full_name.as_str()) == name))
+        })?;
+        Some(SystemFontId(face.id))
+    }
+
+    pub fn load_local(&self, id: SystemFontId) -> Option<hb::Shared<hb::Font<'static>>> {
+        let (blob, index) = self.index().load_blob(id.0)?;
+        Some(hb::Font::new(hb::Face::new(blob, index)).into())
+    }
+}
@@ -1,39 +1,55 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::CStr;
+use std::hash::Hash;
 use std::num::NonZeroU64;
 use std::ops::Range;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::{process, ptr};
+use std::{mem, process, ptr};
 
 use cssparser::ToCss as _;
 use deno_core::{GarbageCollected, OpState, op2, v8};
 use euclid::default::{Box2D, Point2D, Transform2D, Vector2D};
 use euclid::{point2, size2, vec2};
 use harfbuzz_rs as hb;
-use hashlink::LinkedHashMap;
+use hashlink::linked_hash_map::Entry;
+use hashlink::{LinkedHashMap, LruCache};
+use itertools::Itertools as _;
+use strum_macros::FromRepr;
 use unicase::UniCase;
-use unicode_bidi::{self as bidi, ParagraphBidiInfo};
+use unicode_bidi::{self as bidi, BidiDataSource as _, ParagraphBidiInfo};
+use unicode_linebreak::BreakOpportunity;
+use unicode_script::{Script, UnicodeScript as _};
+use unicode_segmentation::UnicodeSegmentation as _;
 
+use super::color_glyph::{ColorGlyphLayer, color_glyph_foreground_key, color_glyph_layers};
+use super::css::color::AbsoluteColor;
 use super::css::font::font_face::{
     ComputedFontStyleRange, ComputedFontWeightRange, ComputedFontWidthRange, SpecifiedFontDisplay,
-    SpecifiedFontFeatureSettings, SpecifiedFontSource, SpecifiedFontSources,
-    SpecifiedFontStyleRange, SpecifiedFontVariationSettings, SpecifiedFontWeightRange,
+    SpecifiedFontFeatureSettings, SpecifiedFontFormat, SpecifiedFontNamedInstance,
+    SpecifiedFontSource, SpecifiedFontSources, SpecifiedFontStyleRange, SpecifiedFontSynthesis,
+    SpecifiedFontTech, SpecifiedFontVariationSettings, SpecifiedFontWeightRange,
     SpecifiedFontWidthRange, SpecifiedMetricsOverride, SpecifiedMetricsOverrideValue,
-    SpecifiedUnicodeRange,
+    SpecifiedSizeAdjust, SpecifiedUnicodeRange,
 };
 use super::css::font::{
     ComputedFamilyName, ComputedFont, ComputedFontStyle, ComputedFontVariantCaps,
-    ComputedFontWeight, ComputedFontWidth, SpecifiedSpecificFamily,
+    ComputedFontVariantLigatures, ComputedFontVariantNumeric, ComputedFontWeight,
+    ComputedFontWidth, ComputedGenericFamily, ComputedSpecificFamily, ComputedSpecificFamilyList,
+    SpecifiedSpecificFamily,
 };
 use super::css::{self, FromCss as _, UnicodeRangeSet};
 use super::error::Canvas2DError;
-use super::path::Path;
+use super::glyph_mask::GlyphMaskCache;
+use super::path::{Path, PathMeasure};
 use super::state::{
-    CanvasDirection, CanvasFontKerning, CanvasTextAlign, CanvasTextBaseline, CanvasTextRendering,
-    DrawingState,
+    CanvasDirection, CanvasFontKerning, CanvasTextAlign, CanvasTextBaseline, CanvasTextPathSide,
+    CanvasTextRendering, DrawingState,
+};
+use super::system_font::{
+    GenericFontFamilies, SystemFontDatabase, SystemFontId, SystemFontOptions, bundled_fallback_font,
 };
 use super::wrap::Wrap;
 
@@ -156,7 +172,15 @@ pub struct FontFaceData {
     ascent_override: SpecifiedMetricsOverride,
     descent_override: SpecifiedMetricsOverride,
     line_gap_override: SpecifiedMetricsOverride,
+    size_adjust: SpecifiedSizeAdjust,
+    synthesis: SpecifiedFontSynthesis,
+    named_instance: SpecifiedFontNamedInstance,
+    local_source: Option<SystemFontId>,
+    face_index: u32,
     state: FontFaceState,
+    /// Bumped whenever something that shaping depends on changes, so that
+    /// cached shaping results are not reused.
+    revision: u64,
 }
 
 impl FontFaceData {
@@ -173,6 +197,9 @@ impl FontFaceData {
         ascent_override: SpecifiedMetricsOverride,
         descent_override: SpecifiedMetricsOverride,
         line_gap_override: SpecifiedMetricsOverride,
+        size_adjust: SpecifiedSizeAdjust,
+        synthesis: SpecifiedFontSynthesis,
+        named_instance: SpecifiedFontNamedInstance,
         state: FontFaceState,
     ) -> Self {
         Self {
@@ -187,7 +214,13 @@ impl FontFaceData {
             ascent_override,
             descent_override,
             line_gap_override,
+            size_adjust,
+            synthesis,
+            named_instance,
+            local_source: None,
+            face_index: 0,
             state,
+            revision: 0,
         }
     }
 
@@ -205,6 +238,7 @@ impl FontFaceData {
 
     pub fn set_style(&mut self, value: SpecifiedFontStyleRange) {
         self.style = CachedStyle::new(value);
+        self.revision += 1;
     }
 
     pub fn weight(&self) -> SpecifiedFontWeightRange {
@@ -213,6 +247,7 @@ impl FontFaceData {
 
     pub fn set_weight(&mut self, value: SpecifiedFontWeightRange) {
         self.weight = CachedWeight::new(value);
+        self.revision += 1;
     }
 
     pub fn width(&self) -> SpecifiedFontWidthRange {
@@ -237,6 +272,7 @@ impl FontFaceData {
 
     pub fn set_feature_settings(&mut self, value: SpecifiedFontFeatureSettings) {
         self.feature_settings = value;
+        self.revision += 1;
     }
 
     pub fn variation_settings(&self) -> SpecifiedFontVariationSettings {
@@ -245,6 +281,7 @@ impl FontFaceData {
 
     pub fn set_variation_settings(&mut self, value: SpecifiedFontVariationSettings) {
         self.variation_settings = value;
+        self.revision += 1;
     }
 
     pub fn display(&self) -> SpecifiedFontDisplay {
@@ -279,6 +316,101 @@ impl FontFaceData {
         self.line_gap_override = value;
     }
 
+    pub fn size_adjust(&self) -> SpecifiedSizeAdjust {
+        self.size_adjust
+    }
+
+    pub fn set_size_adjust(&mut self, value: SpecifiedSizeAdjust) {
+        self.size_adjust = value;
+        self.revision += 1;
+    }
+
+    pub fn synthesis(&self) -> SpecifiedFontSynthesis {
+        self.synthesis
+    }
+
+    pub fn set_synthesis(&mut self, value: SpecifiedFontSynthesis) {
+        self.synthesis = value;
+        self.revision += 1;
+    }
+
+    pub fn named_instance(&self) -> SpecifiedFontNamedInstance {
+        self.named_instance.clone()
+    }
+
+    pub fn set_named_instance(&mut self, value: SpecifiedFontNamedInstance) {
+        self.named_instance = value;
+        self.revision += 1;
+    }
+
+    /// Whether the loaded font is a variable font with the axis `tag`.
+    pub fn has_variation_axis(&self, tag: &[u8; 4]) -> bool {
+        match self.state {
+            FontFaceState::Loaded(ref font) => font.face().find_variation_axis_info(tag).is_some(),
+            _ => false,
+        }
+    }
+
+    /// The variation axes of the loaded font, in the order of its `fvar` table.
+    pub fn variation_axes(&self) -> Vec<VariationAxis> {
+        match self.state {
+            FontFaceState::Loaded(ref font) => {
+                with_parsed_face(&font.face(), variation_axes).unwrap_or_default()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// The named instances of the loaded font, in the order of its `fvar`
+    /// table.
+    pub fn named_instances(&self) -> Vec<NamedInstance> {
+        match self.state {
+            FontFaceState::Loaded(ref font) => with_parsed_face(&font.face(), named_instances)
+                .flatten()
+                .unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+
+    /// Whether the loaded font has colour glyphs that can be drawn (`COLR`,
+    /// `CBDT` or `sbix` tables), which is how emoji fonts are told apart from
+    /// text fonts. `SVG ` glyphs are not supported, so fonts with only those
+    /// count as text fonts.
+    pub fn has_color_glyphs(&self) -> bool {
+        match self.state {
+            FontFaceState::Loaded(ref font) => [b"COLR", b"CBDT", b"sbix"]
+                .into_iter()
+                .any(|tag| font.face().table_with_tag(tag).is_some()),
+            _ => false,
+        }
+    }
+
+    pub fn set_local_source(&mut self, value: SystemFontId) {
+        self.local_source = Some(value);
+    }
+
+    /// Selects the face to load when the source is a font collection
+    /// (`.ttc`/`.otc`).
+    pub fn set_face_index(&mut self, value: u32) {
+        self.face_index = value;
+    }
+
+    pub fn load_local(&mut self, system_fonts: &SystemFontDatabase) -> Result<(), Canvas2DError> {
+        match self.state {
+            FontFaceState::Unloaded => {
+                let font = self
+                    .local_source
+                    .take()
+                    .and_then(|id| system_fonts.load_local(id))
+                    .ok_or(Canvas2DError::LocalFontNotFound)?;
+                self.state = FontFaceState::Loaded(font);
+                self.revision += 1;
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+
     pub fn load(&mut self, blob: &[u8], from_url: bool) -> Result<(), Canvas2DError> {
         match self.state {
             FontFaceState::Unloaded => {
@@ -289,7 +421,16 @@ impl FontFaceData {
                         Canvas2DError::DecodeFont
                     }
                 })?;
-                self.state = FontFaceState::Loaded(hb::Font::new(hb::Face::new(blob, 0)).into());
+                if self.face_index >= ttf_parser::fonts_in_collection(&blob).unwrap_or(1) {
+                    return Err(if from_url {
+                        Canvas2DError::DecodeFontFromUrl
+                    } else {
+                        Canvas2DError::DecodeFont
+                    });
+                }
+                let face = hb::Face::new(blob, self.face_index);
+                self.state = FontFaceState::Loaded(hb::Font::new(face).into());
+                self.revision += 1;
                 Ok(())
             }
             _ => unreachable!(),
@@ -336,26 +477,79 @@ pub struct FontAttributes {
     pub width: ComputedFontWidth,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct FontFaceSet {
     entries: LinkedHashMap<FontFaceId, Rc<FontFace>>,
+    system_fonts: SystemFontDatabase,
+    generic_families: GenericFontFamilies,
+    fallback: Option<Rc<FontFace>>,
+    shaping_cache: RefCell<ShapingCache>,
+    glyph_masks: Rc<RefCell<GlyphMaskCache>>,
 }
 
 impl FontFaceSet {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(
+        system_font_options: SystemFontOptions,
+        generic_families: GenericFontFamilies,
+    ) -> Self {
+        Self {
+            entries: LinkedHashMap::new(),
+            system_fonts: SystemFontDatabase::new(system_font_options),
+            generic_families,
+            fallback: bundled_fallback_font(),
+            shaping_cache: RefCell::new(ShapingCache::new()),
+            glyph_masks: Rc::new(RefCell::new(GlyphMaskCache::new())),
+        }
+    }
+
+    pub fn system_fonts(&self) -> &SystemFontDatabase {
+        &self.system_fonts
+    }
+
+    pub fn set_generic_family(
+        &mut self,
+        generic: ComputedGenericFamily,
+        families: impl IntoIterator<Item = String>,
+    ) {
+        self.generic_families.set(generic, families)
+    }
+
+    fn family_candidates(&self, family: &str) -> Vec<Rc<FontFace>> {
+        let casefolded_family = UniCase::new(family).to_folded_case();
+        // Fonts in the set shadow installed fonts of the same family.
+        let candidates = self
+            .entries
+            .values()
+            .rev()
+            .filter(|font| font.data.borrow().family.casefolded == casefolded_family)
+            .cloned()
+            .collect::<Vec<_>>();
+        if !candidates.is_empty() {
+            return candidates;
+        }
+        self.system_fonts.match_family(&casefolded_family).to_vec()
     }
 
     pub fn insert(&mut self, font: Rc<FontFace>) {
         self.entries.insert(font.id, font);
+        self.shaping_cache.get_mut().clear();
+        self.glyph_masks.borrow_mut().clear();
     }
 
     pub fn remove(&mut self, id: FontFaceId) {
         self.entries.remove(&id);
+        self.shaping_cache.get_mut().clear();
+        self.glyph_masks.borrow_mut().clear();
     }
 
     pub fn clear(&mut self) {
         self.entries.clear();
+        self.shaping_cache.get_mut().clear();
+        self.glyph_masks.borrow_mut().clear();
+    }
+
+    pub fn glyph_masks(&self) -> Rc<RefCell<GlyphMaskCache>> {
+        self.glyph_masks.clone()
     }
 
     pub fn match_fonts(
@@ -464,18 +658,22 @@ impl FontFaceSet {
             }
         }
 
-        let family = match *family {
-            ComputedFamilyName::Specific(ref v) => &v.name,
-            ComputedFamilyName::Generic(_) => return vec![], // TODO actually select a family
+        let candidates = match *family {
+            ComputedFamilyName::Specific(ref v) => self.family_candidates(&v.name),
+            // A generic family resolves to the first concrete family in its
+            // table entry that has any faces at all.
+            ComputedFamilyName::Generic(generic) => self
+                .generic_families
+                .get(generic)
+                .iter()
+                .map(|family| self.family_candidates(family))
+                .find(|candidates| !candidates.is_empty())
+                .unwrap_or_default(),
         };
-        let casefolded_family = UniCase::new(family).to_folded_case();
         let mut result = Vec::new();
         let mut min_distance = [(u8::MAX, f32::INFINITY); 3];
-        for font in self.entries.values().rev() {
+        for font in candidates {
             let data = font.data.borrow();
-            if casefolded_family != data.family.casefolded {
-                continue;
-            }
             let distance = [
                 width_distance(attrs.width, data.width.computed),
                 style_distance(attrs.style, data.style.computed),
@@ -495,15 +693,18 @@ impl FontFaceSet {
         family: &[ComputedFamilyName],
         attrs: FontAttributes,
     ) -> Option<Rc<FontFace>> {
-        family.iter().find_map(|family| {
-            self.match_fonts(family, attrs).into_iter().find(|font| {
-                let data = font.data.borrow();
-                matches!(
-                    data.state,
-                    FontFaceState::Loaded(_) if data.unicode_range.simplified.contains(' ' as u32),
-                )
+        family
+            .iter()
+            .find_map(|family| {
+                self.match_fonts(family, attrs).into_iter().find(|font| {
+                    let data = font.data.borrow();
+                    matches!(
+                        data.state,
+                        FontFaceState::Loaded(_) if data.unicode_range.simplified.contains(' ' as u32),
+                    )
+                })
             })
-        })
+            .or_else(|| self.fallback.clone())
     }
 
     pub fn find_available_font_for_char(
@@ -512,16 +713,95 @@ impl FontFaceSet {
         family: &[ComputedFamilyName],
         attrs: FontAttributes,
     ) -> Option<Rc<FontFace>> {
-        family.iter().find_map(|family| {
-            self.match_fonts(family, attrs).into_iter().find(|font| {
-                let data = font.data.borrow();
-                matches!(
-                    data.state,
-                    FontFaceState::Loaded(ref font)
-                        if data.unicode_range.simplified.contains(c as u32)
-                            && font.get_nominal_glyph(c).is_some(),
-                )
+        family
+            .iter()
+            .find_map(|family| {
+                self.match_fonts(family, attrs).into_iter().find(|font| {
+                    let data = font.data.borrow();
+                    matches!(
+                        data.state,
+                        FontFaceState::Loaded(ref font)
+                            if data.unicode_range.simplified.contains(c as u32)
+                                && font.get_nominal_glyph(c).is_some(),
+                    )
+                })
+            })
+            .or_else(|| {
+                // None of the requested families cover `c`, so try the
+                // closest face of any installed family that does.
+                let family = ComputedFamilyName::Specific(ComputedSpecificFamily {
+                    name: self.system_fonts.find_family_for_char(c, attrs)?,
+                });
+                self.match_fonts(&family, attrs).into_iter().find(|font| {
+                    let data = font.data.borrow();
+                    matches!(
+                        data.state,
+                        FontFaceState::Loaded(ref font) if font.get_nominal_glyph(c).is_some(),
+                    )
+                })
             })
+            // Even without a glyph for `c`, the fallback font draws `.notdef`
+            // rather than dropping the character.
+            .or_else(|| self.fallback.clone())
+    }
+
+    /// Like [`Self::find_available_font_for_char`], but for an extended
+    /// grapheme cluster, so that combining marks, emoji modifiers and ZWJ
+    /// sequences are shaped with the same font as their base. A trailing VS15
+    /// or VS16 prefers a font without or with colour glyphs respectively, and
+    /// VS16 also consults the `emoji` generic family first.
+    pub fn find_available_font_for_cluster(
+        &self,
+        cluster: &str,
+        family: &[ComputedFamilyName],
+        attrs: FontAttributes,
+    ) -> Option<Rc<FontFace>> {
+        const VS15: char = '\u{FE0E}';
+        const VS16: char = '\u{FE0F}';
+        let emoji_presentation = cluster
+            .chars()
+            .rev()
+            .find(|&c| c == VS15 || c == VS16)
+            .map(|c| c == VS16);
+        let emoji_family = [ComputedFamilyName::Generic(ComputedGenericFamily::Emoji)];
+        let families = match emoji_presentation {
+            Some(true) => &emoji_family[..],
+            _ => &emoji_family[..0],
+        }
+        .iter()
+        .chain(family);
+        let candidates = || {
+            families
+                .clone()
+                .flat_map(|family| self.match_fonts(family, attrs))
+        };
+        let covers_cluster = |font: &Rc<FontFace>| {
+            let data = font.data.borrow();
+            let FontFaceState::Loaded(ref font) = data.state else {
+                return false;
+            };
+            cluster
+                .chars()
+                // Joiners and variation selectors are handled by the shaper
+                // and often have no glyph of their own.
+                .filter(|&c| !is_cluster_extender(c))
+                .all(|c| {
+                    data.unicode_range.simplified.contains(c as u32)
+                        && font.get_nominal_glyph(c).is_some()
+                })
+        };
+        let found = match emoji_presentation {
+            Some(emoji_presentation) => candidates()
+                .find(|font| {
+                    covers_cluster(font)
+                        && font.data.borrow().has_color_glyphs() == emoji_presentation
+                })
+                .or_else(|| candidates().find(covers_cluster)),
+            None => candidates().find(covers_cluster),
+        };
+        found.or_else(|| {
+            let base = cluster.chars().next()?;
+            self.find_available_font_for_char(base, family, attrs)
         })
     }
 
@@ -544,6 +824,13 @@ impl FontFaceSet {
     }
 }
 
+fn is_cluster_extender(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200C}' | '\u{200D}' | '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
 // SAFETY: this type has no members.
 unsafe impl GarbageCollected for Wrap<Rc<RefCell<FontFaceSet>>> {
     fn get_name(&self) -> &'static CStr {
@@ -553,7 +840,7 @@ unsafe impl GarbageCollected for Wrap<Rc<RefCell<FontFaceSet>>> {
     fn trace(&self, _: &mut v8::cppgc::Visitor) {}
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Direction {
     Ltr,
     Rtl,
@@ -652,6 +939,9 @@ fn replace_ascii_whitespace(s: &str) -> Cow<'_, str> {
 struct FontMetrics {
     pub ascent: f32,
     pub descent: f32,
+    pub line_gap: f32,
+    pub cap_height: f32,
+    pub x_height: f32,
     pub em_ascent: f32,
     pub em_descent: f32,
     pub hanging_baseline: f32,
@@ -664,6 +954,7 @@ impl FontMetrics {
         font: &hb::Font,
         ascent_override: Option<f32>,
         descent_override: Option<f32>,
+        line_gap_override: Option<f32>,
     ) -> Self {
         let unit = 1.0 / font.face().upem() as f32;
         let ascent = ascent_override.unwrap_or_else(|| match font.get_position(b"hasc") {
@@ -674,6 +965,20 @@ impl FontMetrics {
             Some(v) => v as f32 * unit,
             None => -0.2,
         });
+        let line_gap = line_gap_override.unwrap_or_else(|| match font.get_position(b"hlgp") {
+            Some(v) => v as f32 * unit,
+            None => 0.0,
+        });
+        // Fall back to the ascent and half an em, as for the `cap` and `ex`
+        // units in CSS.
+        let cap_height = match font.get_position(b"cpht") {
+            Some(v) => v as f32 * unit,
+            None => ascent,
+        };
+        let x_height = match font.get_position(b"xhgt") {
+            Some(v) => v as f32 * unit,
+            None => 0.5,
+        };
         let os2_ascent = match font.get_position(b"Oasc") {
             Some(v) => v as f32 * unit,
             None => 0.8,
@@ -703,6 +1008,9 @@ impl FontMetrics {
         Self {
             ascent,
             descent,
+            line_gap,
+            cap_height,
+            x_height,
             em_ascent,
             em_descent,
             hanging_baseline,
@@ -715,6 +1023,9 @@ impl FontMetrics {
         Self {
             ascent: 0.0,
             descent: 0.0,
+            line_gap: 0.0,
+            cap_height: 0.0,
+            x_height: 0.0,
             em_ascent: 0.0,
             em_descent: 0.0,
             hanging_baseline: 0.0,
@@ -727,6 +1038,9 @@ impl FontMetrics {
         Self {
             ascent: self.ascent * scale,
             descent: self.descent * scale,
+            line_gap: self.line_gap * scale,
+            cap_height: self.cap_height * scale,
+            x_height: self.x_height * scale,
             em_ascent: self.em_ascent * scale,
             em_descent: self.em_descent * scale,
             hanging_baseline: self.hanging_baseline * scale,
@@ -750,6 +1064,12 @@ pub struct TextMetrics {
     pub hanging_baseline: f32,
     pub alphabetic_baseline: f32,
     pub ideographic_baseline: f32,
+    /// The line gap recommended by the first available font.
+    pub font_line_gap: f32,
+    /// The height of capital letters above the alphabetic baseline.
+    pub cap_height: f32,
+    /// The height of lowercase letters above the alphabetic baseline.
+    pub x_height: f32,
 }
 
 impl TextMetrics {
@@ -767,6 +1087,9 @@ impl TextMetrics {
             hanging_baseline: 0.0,
             alphabetic_baseline: 0.0,
             ideographic_baseline: 0.0,
+            font_line_gap: 0.0,
+            cap_height: 0.0,
+            x_height: 0.0,
         }
     }
 }
@@ -775,9 +1098,277 @@ impl TextMetrics {
 struct TextRun {
     range: Range<usize>,
     direction: Direction,
+    script: Script,
     font: Option<Rc<FontFace>>,
 }
 
+fn is_common_script(script: Script) -> bool {
+    matches!(script, Script::Common | Script::Inherited | Script::Unknown)
+}
+
+/// Splits `text` into maximal runs of a single script, returned as the start
+/// offset and script of each run. Characters of the Common and Inherited
+/// scripts take the script of the preceding character, except that paired
+/// brackets take the script in effect at the opening bracket; leading ones
+/// take the script of the first character that has one (UAX #24).
+fn itemize_scripts(text: &str) -> Vec<(usize, Script)> {
+    const MAX_BRACKET_DEPTH: usize = 64;
+    let mut resolved = Vec::<(usize, Option<Script>)>::new();
+    let mut brackets = Vec::<(char, Option<Script>)>::new();
+    let mut current = None;
+    for (i, c) in text.char_indices() {
+        let script = c.script();
+        if !is_common_script(script) {
+            if current.is_none() {
+                let pending = resolved.iter_mut().map(|(_, script)| script);
+                for pending in pending.chain(brackets.iter_mut().map(|(_, script)| script)) {
+                    *pending = Some(script);
+                }
+            }
+            current = Some(script);
+        } else if let Some(bracket) = bidi::HardcodedBidiData.bidi_matched_opening_bracket(c) {
+            if bracket.is_open {
+                if brackets.len() < MAX_BRACKET_DEPTH {
+                    brackets.push((bracket.opening, current));
+                }
+            } else if let Some(index) = brackets
+                .iter()
+                .rposition(|&(opening, _)| opening == bracket.opening)
+            {
+                current = brackets[index].1;
+                brackets.truncate(index);
+            }
+        }
+        resolved.push((i, current));
+    }
+    let mut runs = Vec::<(usize, Script)>::new();
+    for (i, script) in resolved {
+        let script = script.unwrap_or(Script::Common);
+        if runs.last().is_none_or(|&(_, last)| last != script) {
+            runs.push((i, script));
+        }
+    }
+    runs
+}
+
+fn script_at(scripts: &[(usize, Script)], index: usize) -> Script {
+    let run = scripts.partition_point(|&(start, _)| start <= index);
+    run.checked_sub(1)
+        .map_or(Script::Common, |run| scripts[run].1)
+}
+
+/// The ISO 15924 tag of `script`, as stored in `DrawingState::script`.
+fn script_tag(script: Script) -> u32 {
+    script
+        .short_name()
+        .as_bytes()
+        .try_into()
+        .map_or(0, u32::from_be_bytes)
+}
+
+/// How many characters around a run HarfBuzz reads as context (its
+/// `HB_BUFFER_CONTEXT_LENGTH`), and thus how much of them a shaped run depends on.
+const SHAPING_CONTEXT_LENGTH: usize = 5;
+
+/// Relative size of synthesized small capitals, as in most browsers.
+const SMALL_CAPS_SCALE: f32 = 0.7;
+
+/// Extra synthetic bold of synthesized small capitals, as a fraction of the em.
+const SMALL_CAPS_EMBOLDEN: f32 = 100.0 / 14400.0;
+
+/// How letters are turned into small capitals when the font has no
+/// substitution for them: they are uppercased and drawn with `font` at
+/// [`SMALL_CAPS_SCALE`] of the font size.
+#[derive(Debug)]
+struct CapsSynthesis {
+    font: hb::Shared<hb::Font<'static>>,
+    lowercase: bool,
+    uppercase: bool,
+}
+
+impl CapsSynthesis {
+    fn applies_to(&self, c: char) -> bool {
+        (self.lowercase && c.is_lowercase()) || (self.uppercase && c.is_uppercase())
+    }
+
+    /// Splits `range` of `text` into pieces that are either all synthesized or
+    /// all left alone, in the order they are to be shaped.
+    fn split_text(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        direction: Direction,
+    ) -> Vec<(Range<usize>, bool)> {
+        let mut segments = Vec::<(Range<usize>, bool)>::new();
+        for (i, g) in text[range.clone()].grapheme_indices(true) {
+            let i = range.start + i;
+            let synthesized = g.chars().next().is_some_and(|c| self.applies_to(c));
+            match segments.last_mut() {
+                Some((range, last)) if *last == synthesized => range.end = i + g.len(),
+                _ => segments.push((i..i + g.len(), synthesized)),
+            }
+        }
+        match direction {
+            Direction::Ltr => {}
+            Direction::Rtl => segments.reverse(),
+        }
+        segments
+    }
+}
+
+/// Enables the features for `caps`, falling back to related ones when the
+/// font lacks them. Returns whether lowercase and uppercase letters are left
+/// for [`CapsSynthesis`].
+fn enable_caps_features(
+    caps: ComputedFontVariantCaps,
+    gsub_features: &HashSet<[u8; 4]>,
+    features: &mut HashMap<hb::Tag, u32>,
+) -> (bool, bool) {
+    let mut enable =
+        |candidates: &[&[u8; 4]]| match candidates.iter().find(|&&tag| gsub_features.contains(tag))
+        {
+            Some(&tag) => {
+                features.insert(tag.into(), 1);
+                false
+            }
+            None => true,
+        };
+    match caps {
+        ComputedFontVariantCaps::Normal => (false, false),
+        ComputedFontVariantCaps::SmallCaps => (enable(&[b"smcp"]), false),
+        ComputedFontVariantCaps::AllSmallCaps => (enable(&[b"smcp"]), enable(&[b"c2sc"])),
+        ComputedFontVariantCaps::PetiteCaps => (enable(&[b"pcap", b"smcp"]), false),
+        ComputedFontVariantCaps::AllPetiteCaps => {
+            (enable(&[b"pcap", b"smcp"]), enable(&[b"c2pc", b"c2sc"]))
+        }
+        ComputedFontVariantCaps::Unicase => (false, enable(&[b"unic", b"c2sc"])),
+        ComputedFontVariantCaps::TitlingCaps => {
+            enable(&[b"titl"]);
+            (false, false)
+        }
+    }
+}
+
+/// A variation axis of a font, from its `fvar` table.
+#[derive(Clone, Debug)]
+pub struct VariationAxis {
+    pub tag: [u8; 4],
+    pub name: Option<String>,
+    pub min_value: f32,
+    pub default_value: f32,
+    pub max_value: f32,
+    pub hidden: bool,
+}
+
+/// A named instance of a variable font, from its `fvar` table.
+#[derive(Clone, Debug)]
+pub struct NamedInstance {
+    pub name: Option<String>,
+    pub postscript_name: Option<String>,
+    /// The value of each axis, in the order of the `fvar` table.
+    pub coordinates: Box<[f32]>,
+}
+
+fn with_parsed_face<R>(face: &hb::Face, f: impl FnOnce(&ttf_parser::Face) -> R) -> Option<R> {
+    let data = face.face_data();
+    let face = ttf_parser::Face::parse(&data, face.index()).ok()?;
+    Some(f(&face))
+}
+
+fn name_by_id(face: &ttf_parser::Face, id: u16) -> Option<String> {
+    face.names()
+        .into_iter()
+        .filter(|name| name.name_id == id)
+        .find_map(|name| name.to_string())
+}
+
+fn variation_axes(face: &ttf_parser::Face) -> Vec<VariationAxis> {
+    face.variation_axes()
+        .into_iter()
+        .map(|axis| VariationAxis {
+            tag: axis.tag.to_bytes(),
+            name: name_by_id(face, axis.name_id),
+            min_value: axis.min_value,
+            default_value: axis.def_value,
+            max_value: axis.max_value,
+            hidden: axis.hidden,
+        })
+        .collect()
+}
+
+/// Reads the instance records of the `fvar` table, which `ttf_parser` skips.
+fn named_instances(face: &ttf_parser::Face) -> Option<Vec<NamedInstance>> {
+    let data = face
+        .raw_face()
+        .table(ttf_parser::Tag::from_bytes(b"fvar"))?;
+    let read_u16 = |offset: usize| Some(u16::from_be_bytes(*data.get(offset..)?.first_chunk()?));
+    let read_fixed = |offset: usize| {
+        Some(i32::from_be_bytes(*data.get(offset..)?.first_chunk()?) as f32 / 65536.0)
+    };
+    let axes_offset = usize::from(read_u16(4)?);
+    let axis_count = usize::from(read_u16(8)?);
+    let axis_size = usize::from(read_u16(10)?);
+    let instance_count = usize::from(read_u16(12)?);
+    let instance_size = usize::from(read_u16(14)?);
+    let instances_offset = axes_offset + axis_count * axis_size;
+    let has_postscript_name = instance_size >= axis_count * 4 + 6;
+    (0..instance_count)
+        .map(|index| {
+            let offset = instances_offset + index * instance_size;
+            let coordinates = (0..axis_count)
+                .map(|axis| read_fixed(offset + 4 + axis * 4))
+                .collect::<Option<_>>()?;
+            let postscript_name = if has_postscript_name {
+                read_u16(offset + 4 + axis_count * 4)
+                    .filter(|&id| id != 0xffff)
+                    .and_then(|id| name_by_id(face, id))
+            } else {
+                None
+            };
+            Some(NamedInstance {
+                name: name_by_id(face, read_u16(offset)?),
+                postscript_name,
+                coordinates,
+            })
+        })
+        .collect()
+}
+
+/// The axis values of the named instance called `name`, which is compared
+/// ignoring ASCII case.
+fn named_instance_coordinates(face: &hb::Face, name: &str) -> Vec<([u8; 4], f32)> {
+    with_parsed_face(face, |face| {
+        let instance = named_instances(face)?.into_iter().find(|instance| {
+            instance
+                .name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        })?;
+        Some(
+            face.variation_axes()
+                .into_iter()
+                .map(|axis| axis.tag.to_bytes())
+                .zip(instance.coordinates)
+                .collect(),
+        )
+    })
+    .flatten()
+    .unwrap_or_default()
+}
+
+/// The tags of the features in the font's `GSUB` table, for any script.
+fn gsub_feature_tags(face: &hb::Face) -> HashSet<[u8; 4]> {
+    with_parsed_face(face, |face| {
+        face.tables()
+            .gsub
+            .into_iter()
+            .flat_map(|gsub| gsub.features)
+            .map(|feature| feature.tag.to_bytes())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
 fn split_text_to_runs(
     fonts: &FontFaceSet,
     font_family: &[ComputedFamilyName],
@@ -789,35 +1380,41 @@ fn split_text_to_runs(
     if bidi_info.levels.is_empty() {
         return vec![];
     }
+    let scripts = itemize_scripts(text);
     let (levels, runs) = bidi_info.visual_runs(0..bidi_info.levels.len());
     runs.into_iter()
         .flat_map(|range| {
             let direction = Direction::from_bidi_level(levels[range.start]);
             let mut runs = Vec::new();
-            let mut font_mapping = text[range.clone()].char_indices().map(|(i, c)| {
+            let mut font_mapping = text[range.clone()].grapheme_indices(true).map(|(i, g)| {
+                let i = range.start + i;
                 (
-                    range.start + i,
-                    fonts.find_available_font_for_char(c, font_family, font_attrs),
+                    i,
+                    script_at(&scripts, i),
+                    fonts.find_available_font_for_cluster(g, font_family, font_attrs),
                 )
             });
-            if let Some((mut start, mut font)) = font_mapping.next() {
-                for (next, next_font) in font_mapping {
+            if let Some((mut start, mut script, mut font)) = font_mapping.next() {
+                for (next, next_script, next_font) in font_mapping {
                     match (&font, &next_font) {
                         (None, None) => continue,
-                        (Some(x), Some(y)) if x.id == y.id => continue,
+                        (Some(x), Some(y)) if x.id == y.id && script == next_script => continue,
                         _ => {}
                     }
                     runs.push(TextRun {
                         range: start..next,
                         direction,
+                        script,
                         font,
                     });
                     start = next;
+                    script = next_script;
                     font = next_font;
                 }
                 runs.push(TextRun {
                     range: start..range.end,
                     direction,
+                    script,
                     font,
                 });
             }
@@ -830,21 +1427,189 @@ fn split_text_to_runs(
         .collect()
 }
 
-pub fn prepare_text(
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum FontStyleKey {
+    Normal,
+    Italic,
+    Oblique(u32),
+}
+
+/// Everything that a configured HarfBuzz font depends on. Floats are stored as
+/// their bits so that the key can be hashed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct FontInstanceKey {
+    font: FontFaceId,
+    revision: u64,
+    weight: u32,
+    width: u32,
+    style: FontStyleKey,
+    variant_caps: ComputedFontVariantCaps,
+    variant_numeric: ComputedFontVariantNumeric,
+    variant_ligatures: ComputedFontVariantLigatures,
+    feature_settings: SpecifiedFontFeatureSettings,
+    variation_settings: SpecifiedFontVariationSettings,
+    /// The font size, for fonts with an `opsz` axis.
+    optical_size: Option<u32>,
+    kerning: CanvasFontKerning,
+    optimize_speed: bool,
+    letter_spacing: bool,
+}
+
+impl FontInstanceKey {
+    fn new(
+        font: &FontFace,
+        drawing_state: &DrawingState,
+        optimize_speed: bool,
+        letter_spacing: bool,
+    ) -> Self {
+        let data = font.data.borrow();
+        Self {
+            font: font.id,
+            revision: data.revision,
+            weight: drawing_state.font_weight.0.to_bits(),
+            width: drawing_state.font_stretch.modernize().0.to_bits(),
+            style: match drawing_state.font_style {
+                ComputedFontStyle::Normal => FontStyleKey::Normal,
+                ComputedFontStyle::Italic => FontStyleKey::Italic,
+                ComputedFontStyle::Oblique(angle) => FontStyleKey::Oblique(angle.deg.to_bits()),
+            },
+            variant_caps: drawing_state.font_variant_caps,
+            variant_numeric: drawing_state.font_variant_numeric,
+            variant_ligatures: drawing_state.font_variant_ligatures,
+            feature_settings: drawing_state.font_feature_settings.clone(),
+            variation_settings: drawing_state.font_variation_settings.clone(),
+            optical_size: data
+                .has_variation_axis(b"opsz")
+                .then(|| drawing_state.font_size.0.px.to_bits()),
+            kerning: drawing_state.font_kerning,
+            optimize_speed,
+            letter_spacing,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FontInstance {
+    font: hb::Shared<hb::Font<'static>>,
+    features: Box<[hb::Feature]>,
+    small_caps: Option<CapsSynthesis>,
+    size_adjust: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ShapedRunKey {
+    instance: FontInstanceKey,
+    synthesized: bool,
+    text: Box<str>,
+    range: Range<usize>,
+    direction: Direction,
+    script: u32,
+    lang: Box<str>,
+}
+
+/// A glyph as positioned by HarfBuzz, in font units. `cluster` is an offset
+/// into the text of its [`ShapedRunKey`].
+#[derive(Clone, Copy, Debug)]
+struct ShapedRunGlyph {
+    glyph: u32,
+    cluster: usize,
+    advance: Vector2D<i32>,
+    offset: Vector2D<i32>,
+}
+
+/// Configured fonts, shaped runs, glyph outlines and colour glyph layers kept
+/// across calls, so that redrawing the same text does not go through HarfBuzz
+/// again. Shaped runs and outlines are in font units and thus shared between
+/// font sizes; colour glyph layers are too, but are keyed by the pixel size
+/// that picks their bitmap strike and by the text colour.
+#[derive(Debug)]
+struct ShapingCache {
+    instances: LruCache<FontInstanceKey, Option<Rc<FontInstance>>>,
+    runs: LruCache<ShapedRunKey, Rc<[ShapedRunGlyph]>>,
+    outlines: LruCache<(FontInstanceKey, bool, u32), Path>,
+    color_layers: LruCache<(FontInstanceKey, bool, u32, u32, [u8; 4]), Vec<ColorGlyphLayer>>,
+}
+
+impl ShapingCache {
+    fn new() -> Self {
+        Self {
+            instances: LruCache::new(64),
+            runs: LruCache::new(1024),
+            outlines: LruCache::new(8192),
+            color_layers: LruCache::new(1024),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.instances.clear();
+        self.runs.clear();
+        self.outlines.clear();
+        self.color_layers.clear();
+    }
+}
+
+pub fn lru_get_or_insert_with<K: Eq + Hash, V>(
+    cache: &mut LruCache<K, V>,
+    key: K,
+    f: impl FnOnce() -> V,
+) -> &mut V {
+    match cache.entry(key) {
+        Entry::Occupied(mut entry) => {
+            entry.to_back();
+            entry.into_mut()
+        }
+        Entry::Vacant(entry) => entry.insert(f()),
+    }
+}
+
+/// Identifies the outline of a glyph at a particular size and offset, for
+/// caching its coverage masks.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GlyphMaskKey {
+    instance: FontInstanceKey,
+    synthesized: bool,
+    glyph: u32,
+    offset: Vector2D<i32>,
+    scale: u32,
+}
+
+#[derive(Debug)]
+struct ShapedGlyph {
+    path: Path,
+    mask_key: GlyphMaskKey,
+    color_layers: Vec<ColorGlyphLayer>,
+    origin: Point2D<f32>,
+    advance: f32,
+    bounds: Option<Box2D<f32>>,
+    cluster: usize,
+    cluster_center: f32,
+    rtl: bool,
+}
+
+#[derive(Debug)]
+struct ShapedText {
+    glyphs: Vec<ShapedGlyph>,
+    compression: f32,
+    anchor: Vector2D<f32>,
+    metrics: TextMetrics,
+}
+
+/// Shapes `text` into glyphs positioned along a single line. Colour glyphs
+/// are only resolved into layers when `foreground` is given, using it as the
+/// text colour.
+fn shape_text(
     fonts: &FontFaceSet,
     drawing_state: &DrawingState,
     text: &str,
     max_width: f32,
-) -> (Path, TextMetrics) {
-    if max_width <= 0.0 {
-        return (Path::new(), TextMetrics::empty());
-    }
+    foreground: Option<AbsoluteColor>,
+) -> ShapedText {
     let text = replace_ascii_whitespace(text);
     let lang = drawing_state
         .lang
         .parse()
         .unwrap_or(hb::Language(ptr::null()));
-    let script = hb::Script::from_iso15924_tag(hb::Tag(drawing_state.script));
+    let script = drawing_state.script;
     let font_size = drawing_state.font_size.0;
     let font_family = drawing_state.font_family.family_list.as_ref();
     let font_attrs = FontAttributes {
@@ -874,17 +1639,26 @@ pub fn prepare_text(
     let runs = split_text_to_runs(fonts, font_family, font_attrs, direction, &text);
     let mut path_builder = TextPathBuilder {
         path: Path::new(),
-        transform: Transform2D::scale(0.0, 0.0),
+        transform: Transform2D::identity(),
     };
+    let mut glyphs = Vec::new();
     let mut cursor = Point2D::zero();
     let mut bounds = Box2D::zero();
-    let mut font_cache = HashMap::new();
+    let mut cache = fonts.shaping_cache.borrow_mut();
+    let cache = &mut *cache;
     let mut text_has_nonzero_advance = false;
     runs.into_iter().fold(hb::UnicodeBuffer::new(), |buf, run| {
         let Some(font) = run.font else {
             return buf;
         };
-        let Some((ref font, ref features)) = *font_cache.entry(font.id).or_insert_with(|| {
+        let has_color_glyphs = foreground.is_some() && font.data.borrow().has_color_glyphs();
+        let instance_key = FontInstanceKey::new(
+            &font,
+            drawing_state,
+            optimize_speed,
+            letter_spacing.px != 0.0,
+        );
+        let instance = lru_get_or_insert_with(&mut cache.instances, instance_key.clone(), || {
             let data = font.data.borrow();
             match data.state {
                 FontFaceState::Loaded(ref font) => {
@@ -902,29 +1676,19 @@ pub fn prepare_text(
                             features.insert((&entry.tag).into(), entry.value);
                         }
                     }
-                    match drawing_state.font_variant_caps {
-                        ComputedFontVariantCaps::Normal => {}
-                        // TODO synthesize `small-caps` and `all-small-caps` if unsupported by font
-                        ComputedFontVariantCaps::SmallCaps => {
-                            features.insert(b"smcp".into(), 1);
-                        }
-                        ComputedFontVariantCaps::AllSmallCaps => {
-                            features.insert(b"c2sc".into(), 1);
-                            features.insert(b"smcp".into(), 1);
-                        }
-                        ComputedFontVariantCaps::PetiteCaps => {
-                            features.insert(b"pcap".into(), 1);
-                        }
-                        ComputedFontVariantCaps::AllPetiteCaps => {
-                            features.insert(b"c2pc".into(), 1);
-                            features.insert(b"pcap".into(), 1);
-                        }
-                        ComputedFontVariantCaps::Unicase => {
-                            features.insert(b"unic".into(), 1);
-                        }
-                        ComputedFontVariantCaps::TitlingCaps => {
-                            features.insert(b"titl".into(), 1);
-                        }
+                    let synthesis = data.synthesis;
+                    let (synthesize_lowercase, synthesize_uppercase) = enable_caps_features(
+                        drawing_state.font_variant_caps,
+                        &gsub_feature_tags(&face),
+                        &mut features,
+                    );
+                    let synthesize_lowercase = synthesize_lowercase && synthesis.small_caps;
+                    let synthesize_uppercase = synthesize_uppercase && synthesis.small_caps;
+                    for tag in drawing_state.font_variant_numeric.features() {
+                        features.insert((&tag).into(), 1);
+                    }
+                    for (tag, enabled) in drawing_state.font_variant_ligatures.features() {
+                        features.insert((&tag).into(), enabled.into());
                     }
                     match drawing_state.font_kerning {
                         CanvasFontKerning::Auto => {}
@@ -942,16 +1706,29 @@ pub fn prepare_text(
                         features.insert(b"hlig".into(), 0);
                         features.insert(b"calt".into(), 0);
                     }
+                    if let Some(ref list) =
+                        drawing_state.font_feature_settings.feature_tag_value_list
+                    {
+                        for entry in list.iter() {
+                            features.insert((&entry.tag).into(), entry.value);
+                        }
+                    }
                     let features = features
                         .into_iter()
                         .map(|(tag, value)| hb::Feature::new(tag, value, ..))
                         .collect::<Box<[_]>>();
                     let mut variations = HashMap::new();
+                    if let SpecifiedFontNamedInstance::Name(ref name) = data.named_instance {
+                        for (tag, value) in named_instance_coordinates(&face, name) {
+                            variations.insert((&tag).into(), value);
+                        }
+                    }
                     let weight = drawing_state.font_weight.0;
+                    let mut embolden = 0.0;
                     if let Some(info) = face.find_variation_axis_info(b"wght") {
                         variations.insert(hb::Tag(info.0.tag), weight);
-                    } else {
-                        let embolden = (weight - data.weight.computed.1).max(0.0) * (1.0 / 14400.0);
+                    } else if synthesis.weight {
+                        embolden = (weight - data.weight.computed.1).max(0.0) * (1.0 / 14400.0);
                         font.set_synthetic_bold(embolden, embolden, false);
                     }
                     let width = drawing_state.font_stretch.modernize().0;
@@ -963,19 +1740,34 @@ pub fn prepare_text(
                         ComputedFontStyle::Italic => {
                             if let Some(info) = face.find_variation_axis_info(b"ital") {
                                 variations.insert(hb::Tag(info.0.tag), 1.0);
-                            } else if data.style.computed == ComputedFontStyleRange::Normal {
+                            } else if synthesis.style
+                                && data.style.computed == ComputedFontStyleRange::Normal
+                            {
                                 font.set_synthetic_slant(0.25);
                             }
                         }
                         ComputedFontStyle::Oblique(angle) => {
                             if let Some(info) = face.find_variation_axis_info(b"slnt") {
                                 variations.insert(hb::Tag(info.0.tag), -angle.deg);
-                            } else if data.style.computed == ComputedFontStyleRange::Normal {
+                            } else if synthesis.style
+                                && data.style.computed == ComputedFontStyleRange::Normal
+                            {
                                 font.set_synthetic_slant(angle.radians().tan());
                             }
                         }
                     }
-                    if let Some(ref list) = data.variation_settings.variation_value_list {
+                    // `font-optical-sizing: auto`.
+                    if let Some(info) = face.find_variation_axis_info(b"opsz") {
+                        variations.insert(hb::Tag(info.0.tag), font_size.px);
+                    }
+                    let variation_settings = [
+                        &data.variation_settings,
+                        &drawing_state.font_variation_settings,
+                    ];
+                    for list in variation_settings
+                        .into_iter()
+                        .filter_map(|settings| settings.variation_value_list.as_ref())
+                    {
                         for entry in list.iter() {
                             if let Some(info) = face.find_variation_axis_info(&entry.tag) {
                                 variations.insert(hb::Tag(info.0.tag), entry.value);
@@ -987,55 +1779,228 @@ pub fn prepare_text(
                         .map(|(tag, value)| hb::Variation::new(tag, value))
                         .collect::<Box<[_]>>();
                     font.set_variations(&variations);
-                    Some((font, features))
+                    let font = hb::Shared::from(font);
+                    let small_caps = (synthesize_lowercase || synthesize_uppercase).then(|| {
+                        // Sub-fonts inherit the variations and synthetic slant of
+                        // their parent. Scaled down glyphs look too light next to
+                        // full-size ones, so they are emboldened some more.
+                        let mut font = hb::Font::create_sub_font(font.clone());
+                        let embolden = embolden + SMALL_CAPS_EMBOLDEN;
+                        font.set_synthetic_bold(embolden, embolden, false);
+                        CapsSynthesis {
+                            font: font.into(),
+                            lowercase: synthesize_lowercase,
+                            uppercase: synthesize_uppercase,
+                        }
+                    });
+                    Some(Rc::new(FontInstance {
+                        font,
+                        features,
+                        small_caps,
+                        size_adjust: data.size_adjust.0,
+                    }))
                 }
                 _ => None,
             }
-        }) else {
+        })
+        .clone();
+        let Some(instance) = instance else {
             return buf;
         };
-        let scale = font_size.px / font.face().upem() as f32;
-        let buf = buf
-            .add_str_item(&text, &text[run.range])
-            .set_direction(run.direction.to_harfbuzz())
-            .set_script(script)
-            .set_language(lang);
-        let buf = hb::shape(font, buf, features);
-        let positions = buf.get_glyph_positions();
-        let infos = buf.get_glyph_infos();
-        let mut cluster_has_nonzero_advance = false;
-        for (index, (&position, &info)) in positions.iter().zip(infos).enumerate() {
-            let glyph = info.codepoint;
-            let advance = vec2(position.x_advance, position.y_advance).cast() * scale;
-            let offset = vec2(position.x_offset, position.y_offset).cast() * scale;
-            let pos = cursor + offset;
-            path_builder.transform = Transform2D::new(scale, 0.0, 0.0, scale, pos.x, pos.y);
-            font.draw_glyph(glyph, &mut path_builder);
-            if let Some(mut extents) = font.get_glyph_extents(glyph) {
-                extents.y_bearing += extents.height;
-                extents.height = -extents.height;
-                bounds = bounds.union(&Box2D::from_origin_and_size(
-                    pos + vec2(extents.x_bearing, extents.y_bearing).cast() * scale,
-                    size2(extents.width, extents.height).cast() * scale,
-                ));
-            }
-            cursor += advance;
-            if advance != Vector2D::zero() {
-                cluster_has_nonzero_advance = true;
-            }
-            if (index + 1 >= infos.len() || info.cluster != infos[index + 1].cluster)
-                && cluster_has_nonzero_advance
-            {
-                cluster_has_nonzero_advance = false;
-                text_has_nonzero_advance = true;
-                let c = text[info.cluster as usize..].chars().next().unwrap();
-                cursor.x += letter_spacing.px;
-                if c == ' ' || c == '\u{a0}' {
-                    cursor.x += word_spacing.px;
+        let FontInstance {
+            ref font,
+            ref features,
+            ref small_caps,
+            size_adjust,
+        } = *instance;
+        let segments = match *small_caps {
+            Some(ref small_caps) => small_caps.split_text(&text, run.range.clone(), run.direction),
+            None => vec![(run.range.clone(), false)],
+        };
+        let script = if is_common_script(run.script) {
+            script
+        } else {
+            script_tag(run.script)
+        };
+        let mut unicode_buf = buf;
+        for (range, synthesized) in segments {
+            let size = font_size.px * size_adjust;
+            let (font, size) = match *small_caps {
+                Some(ref small_caps) if synthesized => (&small_caps.font, size * SMALL_CAPS_SCALE),
+                _ => (font, size),
+            };
+            let scale = size / font.face().upem() as f32;
+            let foreground = foreground.filter(|_| has_color_glyphs);
+            // Runs are cached with only the context that HarfBuzz looks at,
+            // so the key stays small and the run is reused wherever it occurs.
+            let context_start = text[..range.start]
+                .char_indices()
+                .rev()
+                .take(SHAPING_CONTEXT_LENGTH)
+                .last()
+                .map_or(range.start, |(i, _)| i);
+            let context_end = text[range.end..]
+                .char_indices()
+                .nth(SHAPING_CONTEXT_LENGTH)
+                .map_or(text.len(), |(i, _)| range.end + i);
+            let context = &text[context_start..context_end];
+            let item = range.start - context_start..range.end - context_start;
+            let run_key = ShapedRunKey {
+                instance: instance_key.clone(),
+                synthesized,
+                text: context.into(),
+                range: item.clone(),
+                direction: run.direction,
+                script,
+                lang: drawing_state.lang.as_str().into(),
+            };
+            let shaped_run = match cache.runs.get(&run_key) {
+                Some(shaped_run) => shaped_run.clone(),
+                None => {
+                    // Synthesized small capitals are shaped from uppercased text,
+                    // whose offsets are mapped back to those of the original text.
+                    let mut offset_map = Vec::new();
+                    let buf = if synthesized {
+                        let mut uppercased = String::new();
+                        for (i, c) in context[item.clone()].char_indices() {
+                            offset_map.push((uppercased.len(), item.start + i));
+                            uppercased.extend(c.to_uppercase());
+                        }
+                        unicode_buf.add_str_item(&uppercased, &uppercased)
+                    } else {
+                        unicode_buf.add_str_item(context, &context[item])
+                    };
+                    let buf = buf
+                        .set_direction(run.direction.to_harfbuzz())
+                        .set_script(hb::Script::from_iso15924_tag(hb::Tag(script)))
+                        .set_language(lang);
+                    let buf = hb::shape(font, buf, features);
+                    let positions = buf.get_glyph_positions();
+                    let infos = buf.get_glyph_infos();
+                    let shaped_run = positions
+                        .iter()
+                        .zip(infos)
+                        .map(|(position, info)| {
+                            let mut cluster = info.cluster as usize;
+                            if synthesized {
+                                let index =
+                                    offset_map.partition_point(|&(offset, _)| offset <= cluster);
+                                cluster = offset_map[index - 1].1;
+                            }
+                            ShapedRunGlyph {
+                                glyph: info.codepoint,
+                                cluster,
+                                advance: vec2(position.x_advance, position.y_advance),
+                                offset: vec2(position.x_offset, position.y_offset),
+                            }
+                        })
+                        .collect::<Rc<[_]>>();
+                    unicode_buf = buf.clear();
+                    cache.runs.insert(run_key, shaped_run.clone());
+                    shaped_run
+                }
+            };
+            let mut cluster_start = (glyphs.len(), cursor.x);
+            let mut cluster_has_nonzero_advance = false;
+            for (index, &shaped) in shaped_run.iter().enumerate() {
+                let glyph = shaped.glyph;
+                let cluster = context_start + shaped.cluster;
+                let advance = shaped.advance.cast() * scale;
+                let offset = shaped.offset.cast() * scale;
+                let pos = cursor + offset;
+                let outline_key = (instance_key.clone(), synthesized, glyph);
+                let outline = lru_get_or_insert_with(&mut cache.outlines, outline_key, || {
+                    font.draw_glyph(glyph, &mut path_builder);
+                    mem::take(&mut path_builder.path)
+                });
+                let transform = Transform2D::new(scale, 0.0, 0.0, scale, offset.x, offset.y);
+                let path = outline.clone().transform(&transform.cast());
+                let color_layers = match foreground {
+                    Some(foreground) => {
+                        let color_layers_key = (
+                            instance_key.clone(),
+                            synthesized,
+                            glyph,
+                            size.to_bits(),
+                            color_glyph_foreground_key(foreground),
+                        );
+                        let layers = lru_get_or_insert_with(
+                            &mut cache.color_layers,
+                            color_layers_key,
+                            || {
+                                with_parsed_face(&font.face(), |face| {
+                                    color_glyph_layers(
+                                        face,
+                                        glyph,
+                                        Transform2D::identity(),
+                                        size,
+                                        foreground,
+                                    )
+                                })
+                                .unwrap_or_default()
+                            },
+                        );
+                        layers
+                            .iter()
+                            .map(|layer| layer.clone().transform(&transform.cast()))
+                            .collect()
+                    }
+                    None => vec![],
+                };
+                let glyph_bounds = font.get_glyph_extents(glyph).map(|mut extents| {
+                    extents.y_bearing += extents.height;
+                    extents.height = -extents.height;
+                    Box2D::from_origin_and_size(
+                        pos + vec2(extents.x_bearing, extents.y_bearing).cast() * scale,
+                        size2(extents.width, extents.height).cast() * scale,
+                    )
+                });
+                if let Some(ref glyph_bounds) = glyph_bounds {
+                    bounds = bounds.union(glyph_bounds);
+                }
+                glyphs.push(ShapedGlyph {
+                    path,
+                    mask_key: GlyphMaskKey {
+                        instance: instance_key.clone(),
+                        synthesized,
+                        glyph,
+                        offset: shaped.offset,
+                        scale: scale.to_bits(),
+                    },
+                    color_layers,
+                    origin: cursor,
+                    advance: advance.x,
+                    bounds: glyph_bounds,
+                    cluster,
+                    cluster_center: 0.0,
+                    rtl: matches!(run.direction, Direction::Rtl),
+                });
+                cursor += advance;
+                if advance != Vector2D::zero() {
+                    cluster_has_nonzero_advance = true;
+                }
+                let next_cluster = shaped_run.get(index + 1).map(|next| next.cluster);
+                if next_cluster != Some(shaped.cluster) {
+                    let cluster_center = (cluster_start.1 + cursor.x) * 0.5;
+                    for glyph in &mut glyphs[cluster_start.0..] {
+                        glyph.cluster_center = cluster_center;
+                    }
+                    if cluster_has_nonzero_advance {
+                        cluster_has_nonzero_advance = false;
+                        text_has_nonzero_advance = true;
+                        let c = text[cluster..].chars().next().unwrap();
+                        let mut spacing = letter_spacing.px;
+                        if c == ' ' || c == '\u{a0}' {
+                            spacing += word_spacing.px;
+                        }
+                        cursor.x += spacing;
+                        glyphs.last_mut().unwrap().advance += spacing;
+                    }
+                    cluster_start = (glyphs.len(), cursor.x);
                 }
             }
         }
-        buf.clear()
+        unicode_buf
     });
     if text_has_nonzero_advance {
         cursor.x -= letter_spacing.px;
@@ -1057,7 +2022,12 @@ pub fn prepare_text(
                         SpecifiedMetricsOverrideValue::Normal => None,
                         SpecifiedMetricsOverrideValue::Percentage(v) => Some(v),
                     };
-                    FontMetrics::new(font, ascent_override, descent_override).scale(font_size.px)
+                    let line_gap_override = match data.line_gap_override.0 {
+                        SpecifiedMetricsOverrideValue::Normal => None,
+                        SpecifiedMetricsOverrideValue::Percentage(v) => Some(v),
+                    };
+                    FontMetrics::new(font, ascent_override, descent_override, line_gap_override)
+                        .scale(font_size.px * data.size_adjust.0)
                 }
                 _ => FontMetrics::empty(),
             }
@@ -1077,10 +2047,7 @@ pub fn prepare_text(
         CanvasTextBaseline::Ideographic => font_metrics.ideographic_baseline,
         CanvasTextBaseline::Bottom => font_metrics.em_descent,
     };
-    let path = path_builder
-        .path
-        .transform(&Transform2D::new(compression, 0.0, 0.0, 1.0, -anchor_x, -anchor_y).cast());
-    let text_metrics = TextMetrics {
+    let metrics = TextMetrics {
         width,
         actual_bounding_box_left: anchor_x - bounds.min.x,
         actual_bounding_box_right: bounds.max.x - anchor_x,
@@ -1093,18 +2060,668 @@ pub fn prepare_text(
         hanging_baseline: font_metrics.hanging_baseline - anchor_y,
         alphabetic_baseline: font_metrics.alphabetic_baseline - anchor_y,
         ideographic_baseline: font_metrics.ideographic_baseline - anchor_y,
+        font_line_gap: font_metrics.line_gap,
+        cap_height: font_metrics.cap_height - font_metrics.alphabetic_baseline,
+        x_height: font_metrics.x_height - font_metrics.alphabetic_baseline,
     };
-    (path, text_metrics)
+    ShapedText {
+        glyphs,
+        compression,
+        anchor: vec2(anchor_x, anchor_y),
+        metrics,
+    }
 }
 
-fn parse_source_or_throw(css: &str) -> Result<SpecifiedFontSources, Canvas2DError> {
-    SpecifiedFontSources::from_css_string(css).map_err(|e| Canvas2DError::ParseCss {
+pub fn prepare_text(
+    fonts: &FontFaceSet,
+    drawing_state: &DrawingState,
+    text: &str,
+    max_width: f32,
+) -> (Path, TextMetrics) {
+    if max_width <= 0.0 {
+        return (Path::new(), TextMetrics::empty());
+    }
+    let shaped = shape_text(fonts, drawing_state, text, max_width, None);
+    let mut path = Path::new();
+    for glyph in shaped.glyphs {
+        let transform = Transform2D::new(
+            shaped.compression,
+            0.0,
+            0.0,
+            1.0,
+            glyph.origin.x * shaped.compression - shaped.anchor.x,
+            glyph.origin.y - shaped.anchor.y,
+        );
+        path.extend(glyph.path.transform(&transform.cast()));
+    }
+    (path, shaped.metrics)
+}
+
+/// Like [`prepare_text`], but colour glyphs are returned as separately painted
+/// layers instead of being part of the outline. `foreground` is the colour
+/// that colour glyphs use for the text colour.
+pub fn prepare_color_text(
+    fonts: &FontFaceSet,
+    drawing_state: &DrawingState,
+    text: &str,
+    max_width: f32,
+    foreground: AbsoluteColor,
+) -> (Path, Vec<ColorGlyphLayer>) {
+    if max_width <= 0.0 {
+        return (Path::new(), vec![]);
+    }
+    let shaped = shape_text(fonts, drawing_state, text, max_width, Some(foreground));
+    let mut path = Path::new();
+    let mut layers = Vec::new();
+    for glyph in shaped.glyphs {
+        let transform = Transform2D::new(
+            shaped.compression,
+            0.0,
+            0.0,
+            1.0,
+            glyph.origin.x * shaped.compression - shaped.anchor.x,
+            glyph.origin.y - shaped.anchor.y,
+        )
+        .cast();
+        if glyph.color_layers.is_empty() {
+            path.extend(glyph.path.transform(&transform));
+        } else {
+            layers.extend(
+                glyph
+                    .color_layers
+                    .into_iter()
+                    .map(|layer| layer.transform(&transform)),
+            );
+        }
+    }
+    (path, layers)
+}
+
+/// A glyph placed for drawing from a coverage mask. `path` is relative to
+/// `origin`, with the y-axis pointing up, while `origin` is relative to the
+/// text anchor, with the y-axis pointing down.
+#[derive(Clone, Debug)]
+pub struct PositionedGlyph {
+    pub key: GlyphMaskKey,
+    pub path: Path,
+    pub origin: Point2D<f32>,
+}
+
+/// Like [`prepare_color_text`], but the glyphs are returned individually, for
+/// drawing from coverage masks. Returns `None` if the text has colour glyphs
+/// or is compressed to fit `max_width`, which are left to the path-based
+/// route.
+pub fn prepare_text_glyphs(
+    fonts: &FontFaceSet,
+    drawing_state: &DrawingState,
+    text: &str,
+    max_width: f32,
+    foreground: AbsoluteColor,
+) -> Option<Vec<PositionedGlyph>> {
+    if max_width <= 0.0 {
+        return Some(vec![]);
+    }
+    let shaped = shape_text(fonts, drawing_state, text, max_width, Some(foreground));
+    if shaped.compression != 1.0 {
+        return None;
+    }
+    shaped
+        .glyphs
+        .into_iter()
+        .map(|glyph| {
+            glyph.color_layers.is_empty().then(|| PositionedGlyph {
+                key: glyph.mask_key,
+                path: glyph.path,
+                origin: point2(
+                    glyph.origin.x - shaped.anchor.x,
+                    shaped.anchor.y - glyph.origin.y,
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Lays out the text along `path` the way SVG `<textPath>` does: each glyph
+/// cluster is placed so that its horizontal center sits at its advance along
+/// the path, rotated to the path's tangent there. Clusters whose center falls
+/// off either end of the path are dropped. With [`CanvasTextPathSide::Right`]
+/// the path is walked from its end, putting the text on the other side.
+///
+/// The result is in the path's coordinate space, ready to be filled or
+/// stroked.
+pub fn prepare_text_on_path(
+    fonts: &FontFaceSet,
+    drawing_state: &DrawingState,
+    text: &str,
+    path: &Path,
+    start_offset: f64,
+    side: CanvasTextPathSide,
+    max_width: f32,
+) -> Path {
+    if max_width <= 0.0 {
+        return Path::new();
+    }
+    let shaped = shape_text(fonts, drawing_state, text, max_width, None);
+    let path = PathMeasure::new(path);
+    let length = path.length();
+    let mut result = Path::new();
+    for glyph in shaped.glyphs {
+        let distance =
+            start_offset + f64::from(glyph.cluster_center * shaped.compression - shaped.anchor.x);
+        if !(0.0..=length).contains(&distance) {
+            continue;
+        }
+        let Some((point, tangent)) = (match side {
+            CanvasTextPathSide::Left => path.point_and_tangent_at_length(distance),
+            CanvasTextPathSide::Right => path
+                .point_and_tangent_at_length(length - distance)
+                .map(|(point, tangent)| (point, -tangent)),
+        }) else {
+            continue;
+        };
+        let local = Transform2D::new(
+            shaped.compression,
+            0.0,
+            0.0,
+            1.0,
+            (glyph.origin.x - glyph.cluster_center) * shaped.compression,
+            glyph.origin.y - shaped.anchor.y,
+        )
+        .cast();
+        // Flips the glyph's y-up outline and turns its baseline onto the tangent.
+        let placement = Transform2D::new(
+            tangent.x, tangent.y, tangent.y, -tangent.x, point.x, point.y,
+        );
+        result.extend(glyph.path.transform(&local.then(&placement)));
+    }
+    result
+}
+
+fn parse_source_or_throw(css: &str) -> Result<SpecifiedFontSources, Canvas2DError> {
+    SpecifiedFontSources::from_css_string(css).map_err(|e| Canvas2DError::ParseCss {
         css: css.to_owned(),
         kind: css::ValueKind::FontSource,
         details: css::SyntaxError::from(e),
     })
 }
 
+/// A glyph of a [`MeasuredText`]. `cluster` is the offset of the first
+/// character it was shaped from in UTF-16 code units; `x` is relative to the
+/// text anchor.
+#[derive(Clone, Copy, Debug)]
+pub struct MeasuredGlyph {
+    pub cluster: usize,
+    pub x: f32,
+    pub advance: f32,
+}
+
+#[derive(Clone, Debug)]
+struct MeasuredCluster {
+    range: Range<usize>,
+    glyphs: Range<usize>,
+    left: f32,
+    right: f32,
+    rtl: bool,
+}
+
+/// The result of `measureText`, with the shaped glyphs kept for the text
+/// editing queries of `TextMetrics`. Offsets are in UTF-16 code units, and
+/// boxes are relative to the text anchor with the y-axis pointing down, like
+/// the other metrics.
+#[derive(Debug)]
+pub struct MeasuredText {
+    metrics: TextMetrics,
+    glyphs: Vec<MeasuredGlyph>,
+    glyph_bounds: Vec<Option<Box2D<f32>>>,
+    /// In visual order.
+    clusters: Vec<MeasuredCluster>,
+}
+
+impl MeasuredText {
+    pub fn new(fonts: &FontFaceSet, drawing_state: &DrawingState, text: &str) -> Self {
+        let shaped = shape_text(fonts, drawing_state, text, f32::INFINITY, None);
+        let mut utf16_offsets = vec![0; text.len() + 1];
+        let mut offset = 0;
+        for (i, c) in text.char_indices() {
+            utf16_offsets[i] = offset;
+            offset += c.len_utf16();
+        }
+        utf16_offsets[text.len()] = offset;
+        let mut starts = shaped
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.cluster)
+            .collect::<Vec<_>>();
+        starts.sort_unstable();
+        starts.dedup();
+        let anchor = shaped.anchor;
+        let mut glyphs = Vec::with_capacity(shaped.glyphs.len());
+        let mut glyph_bounds = Vec::with_capacity(shaped.glyphs.len());
+        let mut clusters = Vec::<MeasuredCluster>::new();
+        for (index, glyph) in shaped.glyphs.iter().enumerate() {
+            let x = glyph.origin.x - anchor.x;
+            glyphs.push(MeasuredGlyph {
+                cluster: utf16_offsets[glyph.cluster],
+                x,
+                advance: glyph.advance,
+            });
+            glyph_bounds.push(glyph.bounds.map(|bounds| {
+                Box2D::new(
+                    point2(bounds.min.x - anchor.x, anchor.y - bounds.max.y),
+                    point2(bounds.max.x - anchor.x, anchor.y - bounds.min.y),
+                )
+            }));
+            match clusters.last_mut() {
+                Some(cluster) if utf16_offsets[glyph.cluster] == cluster.range.start => {
+                    cluster.glyphs.end = index + 1;
+                    cluster.left = cluster.left.min(x);
+                    cluster.right = cluster.right.max(x + glyph.advance);
+                }
+                _ => {
+                    let next = starts.partition_point(|&start| start <= glyph.cluster);
+                    let end = starts.get(next).copied().unwrap_or(text.len());
+                    clusters.push(MeasuredCluster {
+                        range: utf16_offsets[glyph.cluster]..utf16_offsets[end],
+                        glyphs: index..index + 1,
+                        left: x,
+                        right: x + glyph.advance,
+                        rtl: glyph.rtl,
+                    });
+                }
+            }
+        }
+        Self {
+            metrics: shaped.metrics,
+            glyphs,
+            glyph_bounds,
+            clusters,
+        }
+    }
+
+    pub fn metrics(&self) -> &TextMetrics {
+        &self.metrics
+    }
+
+    pub fn glyphs(&self) -> &[MeasuredGlyph] {
+        &self.glyphs
+    }
+
+    fn clusters_in(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> impl Iterator<Item = (usize, &MeasuredCluster)> {
+        self.clusters
+            .iter()
+            .enumerate()
+            .filter(move |(_, cluster)| cluster.range.start < end && cluster.range.end > start)
+    }
+
+    /// The boxes covering the clusters between `start` and `end`, one for each
+    /// visually contiguous piece, spanning the font bounding box vertically.
+    pub fn selection_rects(&self, start: usize, end: usize) -> Vec<Box2D<f32>> {
+        let top = -self.metrics.font_bounding_box_ascent;
+        let bottom = self.metrics.font_bounding_box_descent;
+        let mut rects = Vec::<Box2D<f32>>::new();
+        let mut last_index = None;
+        for (index, cluster) in self.clusters_in(start, end) {
+            match rects.last_mut() {
+                Some(rect) if last_index.is_some_and(|last| last + 1 == index) => {
+                    rect.max.x = cluster.right;
+                }
+                _ => rects.push(Box2D::new(
+                    point2(cluster.left, top),
+                    point2(cluster.right, bottom),
+                )),
+            }
+            last_index = Some(index);
+        }
+        rects
+    }
+
+    /// The union of the ink bounds of the clusters between `start` and `end`.
+    pub fn actual_bounding_box(&self, start: usize, end: usize) -> Box2D<f32> {
+        self.clusters_in(start, end)
+            .flat_map(|(_, cluster)| &self.glyph_bounds[cluster.glyphs.clone()])
+            .flatten()
+            .copied()
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_else(Box2D::zero)
+    }
+
+    /// The caret position closest to `x`: the nearer edge of the cluster under
+    /// `x`, or the outer edge of the cluster at either end of the line.
+    pub fn index_from_offset(&self, x: f32) -> usize {
+        let edge = |cluster: &MeasuredCluster, left: bool| {
+            if left != cluster.rtl {
+                cluster.range.start
+            } else {
+                cluster.range.end
+            }
+        };
+        let (Some(first), Some(last)) = (self.clusters.first(), self.clusters.last()) else {
+            return 0;
+        };
+        if x < first.left {
+            return edge(first, true);
+        }
+        self.clusters
+            .iter()
+            .find(|cluster| x < cluster.right)
+            .map_or_else(
+                || edge(last, false),
+                |cluster| edge(cluster, x < (cluster.left + cluster.right) * 0.5),
+            )
+    }
+}
+
+// SAFETY: this type has no members.
+unsafe impl GarbageCollected for Wrap<MeasuredText> {
+    fn get_name(&self) -> &'static CStr {
+        c"TextMetrics"
+    }
+
+    fn trace(&self, _: &mut v8::cppgc::Visitor) {}
+}
+
+#[derive(Clone, Copy, Debug, FromRepr)]
+#[repr(i32)]
+pub enum TextLayoutAlign {
+    Start,
+    End,
+    Left,
+    Right,
+    Center,
+    Justify,
+}
+
+#[derive(Clone, Debug)]
+pub struct TextLayoutOptions {
+    pub max_width: f32,
+    pub line_height: Option<f32>,
+    pub align: TextLayoutAlign,
+    pub max_lines: Option<usize>,
+    pub ellipsis: String,
+}
+
+/// A line of a [`TextLayout`]. `start` and `end` are offsets into the laid out
+/// text in UTF-16 code units; `x` and `baseline` are relative to the top left
+/// corner of the layout.
+#[derive(Clone, Copy, Debug)]
+pub struct TextLayoutLine {
+    pub start: usize,
+    pub end: usize,
+    pub x: f32,
+    pub baseline: f32,
+    pub width: f32,
+}
+
+#[derive(Debug)]
+struct BrokenLine {
+    range: Range<usize>,
+    ends_paragraph: bool,
+}
+
+/// Greedily breaks `text` into lines no wider than `max_width` at the UAX #14
+/// break opportunities, falling back to cluster boundaries for words that do
+/// not fit on a line by themselves. `measure` gives the width of a range with
+/// its trailing whitespace hung.
+fn break_lines(
+    text: &str,
+    max_width: f32,
+    boundaries: &[usize],
+    measure: impl Fn(usize, usize) -> f32,
+) -> Vec<BrokenLine> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    let mut last_break = None;
+    for (pos, opportunity) in unicode_linebreak::linebreaks(text) {
+        if measure(line_start, pos) > max_width {
+            if let Some(last_break) = last_break.take() {
+                lines.push(BrokenLine {
+                    range: line_start..last_break,
+                    ends_paragraph: false,
+                });
+                line_start = last_break;
+            }
+            while measure(line_start, pos) > max_width {
+                let candidates = boundaries
+                    .iter()
+                    .copied()
+                    .filter(|&b| b > line_start && b < pos);
+                let Some(end) = candidates
+                    .clone()
+                    .take_while(|&b| measure(line_start, b) <= max_width)
+                    .last()
+                    .or_else(|| candidates.clone().next())
+                else {
+                    break;
+                };
+                lines.push(BrokenLine {
+                    range: line_start..end,
+                    ends_paragraph: false,
+                });
+                line_start = end;
+            }
+        }
+        match opportunity {
+            BreakOpportunity::Mandatory => {
+                lines.push(BrokenLine {
+                    range: line_start..pos,
+                    ends_paragraph: true,
+                });
+                line_start = pos;
+                last_break = None;
+            }
+            BreakOpportunity::Allowed => last_break = Some(pos),
+        }
+    }
+    lines
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+/// A paragraph of text broken into lines, shaped with the font settings of the
+/// drawing state it was created from.
+#[derive(Debug)]
+pub struct TextLayout {
+    path: Path,
+    lines: Vec<TextLayoutLine>,
+    width: f32,
+    height: f32,
+    truncated: bool,
+}
+
+impl TextLayout {
+    pub fn new(
+        fonts: &FontFaceSet,
+        drawing_state: &DrawingState,
+        text: &str,
+        options: &TextLayoutOptions,
+    ) -> Self {
+        let paragraph = shape_text(fonts, drawing_state, text, f32::INFINITY, None);
+        let ascent = paragraph.metrics.font_bounding_box_ascent + paragraph.anchor.y;
+        let descent = paragraph.metrics.font_bounding_box_descent - paragraph.anchor.y;
+        let mut offsets = vec![0.0; text.len() + 1];
+        for glyph in &paragraph.glyphs {
+            offsets[glyph.cluster + 1] += glyph.advance;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        let mut boundaries = paragraph
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.cluster)
+            .collect::<Vec<_>>();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        let hung_end = |start: usize, end: usize| start + text[start..end].trim_end().len();
+        let measure = |start: usize, end: usize| offsets[hung_end(start, end)] - offsets[start];
+        let mut broken = break_lines(text, options.max_width, &boundaries, measure);
+        let mut truncated = false;
+        if let Some(max_lines) = options.max_lines
+            && broken.len() > max_lines
+        {
+            broken.truncate(max_lines);
+            truncated = true;
+            if let Some(last) = broken.last_mut() {
+                let ellipsis_width = if options.ellipsis.is_empty() {
+                    0.0
+                } else {
+                    shape_text(fonts, drawing_state, &options.ellipsis, f32::INFINITY, None)
+                        .metrics
+                        .width
+                };
+                let start = last.range.start;
+                let end = hung_end(start, last.range.end);
+                if measure(start, end) + ellipsis_width > options.max_width {
+                    last.range.end = boundaries
+                        .iter()
+                        .copied()
+                        .filter(|&b| b > start && b < end)
+                        .take_while(|&b| measure(start, b) + ellipsis_width <= options.max_width)
+                        .last()
+                        .unwrap_or(start);
+                }
+                last.ends_paragraph = true;
+            }
+        }
+        let line_count = broken.len();
+        let mut shaped_lines = broken
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let content = &text[line.range.start..hung_end(line.range.start, line.range.end)];
+                let content = if truncated && index + 1 == line_count {
+                    Cow::Owned(format!("{content}{}", options.ellipsis))
+                } else {
+                    Cow::Borrowed(content)
+                };
+                let shaped = shape_text(fonts, drawing_state, &content, f32::INFINITY, None);
+                (line, content, shaped)
+            })
+            .collect::<Vec<_>>();
+        let box_width = if options.max_width.is_finite() {
+            options.max_width
+        } else {
+            shaped_lines
+                .iter()
+                .map(|(_, _, shaped)| shaped.metrics.width)
+                .fold(0.0, f32::max)
+        };
+        let rtl = matches!(drawing_state.direction, CanvasDirection::Rtl);
+        let line_gap = paragraph.metrics.font_line_gap;
+        let line_height = options.line_height.unwrap_or(ascent + descent + line_gap);
+        let half_leading = (line_height - (ascent + descent)) * 0.5;
+        let mut path = Path::new();
+        let mut lines = Vec::with_capacity(shaped_lines.len());
+        let mut width = 0.0f32;
+        for (index, (line, content, shaped)) in shaped_lines.iter_mut().enumerate() {
+            let is_space = |cluster: usize| content[cluster..].starts_with(' ');
+            let mut line_width = shaped.metrics.width;
+            let mut extra_per_space = 0.0;
+            if matches!(options.align, TextLayoutAlign::Justify) && !line.ends_paragraph {
+                let spaces = shaped
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.cluster)
+                    .dedup()
+                    .filter(|&cluster| is_space(cluster))
+                    .count();
+                if spaces > 0 && box_width > line_width {
+                    extra_per_space = (box_width - line_width) / spaces as f32;
+                    line_width = box_width;
+                }
+            }
+            let alignment = match (options.align, rtl) {
+                (TextLayoutAlign::Left, _)
+                | (TextLayoutAlign::Start | TextLayoutAlign::Justify, false)
+                | (TextLayoutAlign::End, true) => PhysicalAlignment::Left,
+                (TextLayoutAlign::Right, _)
+                | (TextLayoutAlign::End, false)
+                | (TextLayoutAlign::Start | TextLayoutAlign::Justify, true) => {
+                    PhysicalAlignment::Right
+                }
+                (TextLayoutAlign::Center, _) => PhysicalAlignment::Center,
+            };
+            let x = match alignment {
+                PhysicalAlignment::Left => 0.0,
+                PhysicalAlignment::Right => box_width - line_width,
+                PhysicalAlignment::Center => (box_width - line_width) * 0.5,
+            };
+            let baseline = index as f32 * line_height + half_leading + ascent;
+            let mut shift = 0.0;
+            let mut previous_cluster = None;
+            for glyph in &mut shaped.glyphs {
+                if let Some(previous_cluster) = previous_cluster
+                    && previous_cluster != glyph.cluster
+                    && is_space(previous_cluster)
+                {
+                    shift += extra_per_space;
+                }
+                let transform = Transform2D::new(
+                    1.0,
+                    0.0,
+                    0.0,
+                    -1.0,
+                    x + shift + glyph.origin.x,
+                    baseline - glyph.origin.y,
+                );
+                path.extend(mem::take(&mut glyph.path).transform(&transform.cast()));
+                previous_cluster = Some(glyph.cluster);
+            }
+            let start = utf16_len(&text[..line.range.start]);
+            lines.push(TextLayoutLine {
+                start,
+                end: start + utf16_len(&text[line.range.clone()]),
+                x,
+                baseline,
+                width: line_width,
+            });
+            width = width.max(line_width);
+        }
+        Self {
+            path,
+            height: lines.len() as f32 * line_height,
+            lines,
+            width,
+            truncated,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn lines(&self) -> &[TextLayoutLine] {
+        &self.lines
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+// SAFETY: this type has no members.
+unsafe impl GarbageCollected for Wrap<TextLayout> {
+    fn get_name(&self) -> &'static CStr {
+        c"TextLayout"
+    }
+
+    fn trace(&self, _: &mut v8::cppgc::Visitor) {}
+}
+
 fn parse_family_or_throw(css: &str) -> Result<SpecifiedSpecificFamily, Canvas2DError> {
     SpecifiedSpecificFamily::from_css_string(css).map_err(|e| Canvas2DError::ParseCss {
         css: css.to_owned(),
@@ -1197,19 +2814,113 @@ fn parse_line_gap_override_or_throw(css: &str) -> Result<SpecifiedMetricsOverrid
     })
 }
 
+fn parse_size_adjust_or_throw(css: &str) -> Result<SpecifiedSizeAdjust, Canvas2DError> {
+    SpecifiedSizeAdjust::from_css_string(css).map_err(|e| Canvas2DError::ParseCss {
+        css: css.to_owned(),
+        kind: css::ValueKind::SizeAdjust,
+        details: css::SyntaxError::from(e),
+    })
+}
+
+fn parse_synthesis_or_throw(css: &str) -> Result<SpecifiedFontSynthesis, Canvas2DError> {
+    SpecifiedFontSynthesis::from_css_string(css).map_err(|e| Canvas2DError::ParseCss {
+        css: css.to_owned(),
+        kind: css::ValueKind::FontSynthesis,
+        details: css::SyntaxError::from(e),
+    })
+}
+
+fn parse_named_instance_or_throw(css: &str) -> Result<SpecifiedFontNamedInstance, Canvas2DError> {
+    SpecifiedFontNamedInstance::from_css_string(css).map_err(|e| Canvas2DError::ParseCss {
+        css: css.to_owned(),
+        kind: css::ValueKind::FontNamedInstance,
+        details: css::SyntaxError::from(e),
+    })
+}
+
+/// Whether a `src` entry with the given `format()` and `tech()` hints can be
+/// loaded. WOFF and WOFF2 are unpacked by the sanitizer, while SVG fonts and
+/// glyphs, Graphite and AAT shaping, palette overrides and incremental
+/// transfer are not implemented.
+fn is_font_source_supported(
+    format: Option<SpecifiedFontFormat>,
+    tech: &[SpecifiedFontTech],
+) -> bool {
+    let format_supported = match format {
+        None
+        | Some(SpecifiedFontFormat::Collection)
+        | Some(SpecifiedFontFormat::Opentype)
+        | Some(SpecifiedFontFormat::Truetype)
+        | Some(SpecifiedFontFormat::Woff)
+        | Some(SpecifiedFontFormat::Woff2) => true,
+        Some(SpecifiedFontFormat::EmbeddedOpentype)
+        | Some(SpecifiedFontFormat::Svg)
+        | Some(SpecifiedFontFormat::Unknown) => false,
+    };
+    format_supported
+        && tech.iter().all(|&tech| match tech {
+            SpecifiedFontTech::FeaturesOpentype
+            | SpecifiedFontTech::ColorColrv0
+            | SpecifiedFontTech::ColorColrv1
+            | SpecifiedFontTech::ColorSbix
+            | SpecifiedFontTech::ColorCbdt
+            | SpecifiedFontTech::Variations => true,
+            SpecifiedFontTech::FeaturesAat
+            | SpecifiedFontTech::FeaturesGraphite
+            | SpecifiedFontTech::ColorSvg
+            | SpecifiedFontTech::Palettes
+            | SpecifiedFontTech::Incremental
+            | SpecifiedFontTech::Unknown => false,
+        })
+}
+
+/// Font collections have no fragment identifier scheme of their own, so a
+/// 1-based index selects the face, as in `url(fonts.ttc#2)`. It takes
+/// precedence over the 0-based `faceIndex` given to the `FontFace`
+/// constructor, which is the only way to pick a face from a buffer.
+fn collection_index_from_url(url: &str) -> Option<u32> {
+    let (_, fragment) = url.rsplit_once('#')?;
+    fragment.parse::<u32>().ok()?.checked_sub(1)
+}
+
 #[op2]
 #[string]
 pub fn op_canvas_2d_font_face_select_source(
+    state: &OpState,
+    #[cppgc] this: &Wrap<Rc<FontFace>>,
     #[string] source: &str,
-) -> Result<String, Canvas2DError> {
+) -> Result<Option<String>, Canvas2DError> {
+    let fonts = state.borrow::<Rc<RefCell<FontFaceSet>>>().borrow();
+    let mut skipped_url = false;
     for source in parse_source_or_throw(source)?.font_source_list.iter() {
         match *source {
-            SpecifiedFontSource::Url(ref url) => return Ok((**url).to_owned()),
-            SpecifiedFontSource::Local(_) => {}
+            SpecifiedFontSource::Url {
+                ref url,
+                format,
+                ref tech,
+            } => {
+                if !is_font_source_supported(format, tech) {
+                    skipped_url = true;
+                    continue;
+                }
+                if let Some(index) = collection_index_from_url(url) {
+                    this.data().borrow_mut().set_face_index(index);
+                }
+                return Ok(Some((**url).to_owned()));
+            }
+            SpecifiedFontSource::Local(ref family) => {
+                if let Some(id) = fonts.system_fonts().find_local(&family.name) {
+                    this.data().borrow_mut().set_local_source(id);
+                    return Ok(None);
+                }
+            }
         }
     }
-    // TODO implement local font fallback
-    Err(Canvas2DError::UnsupportedLocalFontFallback)
+    Err(if skipped_url {
+        Canvas2DError::UnsupportedFontSource
+    } else {
+        Canvas2DError::LocalFontNotFound
+    })
 }
 
 #[op2]
@@ -1226,8 +2937,12 @@ pub fn op_canvas_2d_font_face_new(
     #[string] ascent_override: String,
     #[string] descent_override: String,
     #[string] line_gap_override: String,
+    #[string] size_adjust: String,
+    #[string] synthesis: String,
+    #[string] named_instance: String,
+    face_index: u32,
 ) -> Result<Wrap<Rc<FontFace>>, Canvas2DError> {
-    Ok(Wrap::new(Rc::new(FontFace::new(FontFaceData::new(
+    let mut data = FontFaceData::new(
         parse_family_or_throw(&family)?,
         parse_style_or_throw(&style)?,
         parse_weight_or_throw(&weight)?,
@@ -1239,8 +2954,13 @@ pub fn op_canvas_2d_font_face_new(
         parse_ascent_override_or_throw(&ascent_override)?,
         parse_descent_override_or_throw(&descent_override)?,
         parse_line_gap_override_or_throw(&line_gap_override)?,
+        parse_size_adjust_or_throw(&size_adjust)?,
+        parse_synthesis_or_throw(&synthesis)?,
+        parse_named_instance_or_throw(&named_instance)?,
         FontFaceState::Unloaded,
-    )))))
+    );
+    data.set_face_index(face_index);
+    Ok(Wrap::new(Rc::new(FontFace::new(data))))
 }
 
 #[op2]
@@ -1258,6 +2978,9 @@ pub fn op_canvas_2d_font_face_errored() -> Wrap<Rc<FontFace>> {
         SpecifiedMetricsOverride::default(),
         SpecifiedMetricsOverride::default(),
         SpecifiedMetricsOverride::default(),
+        SpecifiedSizeAdjust::default(),
+        SpecifiedFontSynthesis::default(),
+        SpecifiedFontNamedInstance::default(),
         FontFaceState::Errored,
     ))))
 }
@@ -1466,6 +3189,126 @@ pub fn op_canvas_2d_font_face_set_line_gap_override(
     Ok(())
 }
 
+#[op2]
+#[string]
+pub fn op_canvas_2d_font_face_size_adjust(#[cppgc] this: &Wrap<Rc<FontFace>>) -> String {
+    let data = this.data().borrow();
+    data.size_adjust().to_css_string()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_font_face_set_size_adjust(
+    #[cppgc] this: &Wrap<Rc<FontFace>>,
+    #[string] value: &str,
+) -> Result<(), Canvas2DError> {
+    let value = parse_size_adjust_or_throw(value)?;
+    let mut data = this.data().borrow_mut();
+    data.set_size_adjust(value);
+    Ok(())
+}
+
+#[op2]
+#[string]
+pub fn op_canvas_2d_font_face_synthesis(#[cppgc] this: &Wrap<Rc<FontFace>>) -> String {
+    let data = this.data().borrow();
+    data.synthesis().to_css_string()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_font_face_set_synthesis(
+    #[cppgc] this: &Wrap<Rc<FontFace>>,
+    #[string] value: &str,
+) -> Result<(), Canvas2DError> {
+    let value = parse_synthesis_or_throw(value)?;
+    let mut data = this.data().borrow_mut();
+    data.set_synthesis(value);
+    Ok(())
+}
+
+#[op2]
+#[string]
+pub fn op_canvas_2d_font_face_named_instance(#[cppgc] this: &Wrap<Rc<FontFace>>) -> String {
+    let data = this.data().borrow();
+    data.named_instance().to_css_string()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_font_face_set_named_instance(
+    #[cppgc] this: &Wrap<Rc<FontFace>>,
+    #[string] value: &str,
+) -> Result<(), Canvas2DError> {
+    let value = parse_named_instance_or_throw(value)?;
+    let mut data = this.data().borrow_mut();
+    data.set_named_instance(value);
+    Ok(())
+}
+
+/// Returns the tag, name, minimum, default and maximum value and whether it is
+/// hidden of each axis, flattened.
+#[op2]
+pub fn op_canvas_2d_font_face_variation_axes<'a>(
+    scope: &mut v8::PinScope<'a, '_>,
+    #[cppgc] this: &Wrap<Rc<FontFace>>,
+) -> v8::Local<'a, v8::Array> {
+    let data = this.data().borrow();
+    let elements = data
+        .variation_axes()
+        .into_iter()
+        .flat_map(|axis| {
+            [
+                v8_string(scope, str::from_utf8(&axis.tag).unwrap_or_default()),
+                v8_optional_string(scope, axis.name.as_deref()),
+                v8::Number::new(scope, axis.min_value as f64).into(),
+                v8::Number::new(scope, axis.default_value as f64).into(),
+                v8::Number::new(scope, axis.max_value as f64).into(),
+                v8::Boolean::new(scope, axis.hidden).into(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    v8::Array::new_with_elements(scope, &elements)
+}
+
+/// Returns the name, PostScript name and array of axis values of each named
+/// instance, flattened.
+#[op2]
+pub fn op_canvas_2d_font_face_named_instances<'a>(
+    scope: &mut v8::PinScope<'a, '_>,
+    #[cppgc] this: &Wrap<Rc<FontFace>>,
+) -> v8::Local<'a, v8::Array> {
+    let data = this.data().borrow();
+    let elements = data
+        .named_instances()
+        .into_iter()
+        .flat_map(|instance| {
+            let coordinates = instance
+                .coordinates
+                .iter()
+                .map(|&value| v8::Number::new(scope, value as f64).into())
+                .collect::<Vec<_>>();
+            [
+                v8_optional_string(scope, instance.name.as_deref()),
+                v8_optional_string(scope, instance.postscript_name.as_deref()),
+                v8::Array::new_with_elements(scope, &coordinates).into(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    v8::Array::new_with_elements(scope, &elements)
+}
+
+fn v8_string<'a>(scope: &mut v8::PinScope<'a, '_>, value: &str) -> v8::Local<'a, v8::Value> {
+    v8::String::new(scope, value).unwrap().into()
+}
+
+fn v8_optional_string<'a>(
+    scope: &mut v8::PinScope<'a, '_>,
+    value: Option<&str>,
+) -> v8::Local<'a, v8::Value> {
+    match value {
+        Some(value) => v8_string(scope, value),
+        None => v8::null(scope).into(),
+    }
+}
+
 #[op2]
 pub fn op_canvas_2d_font_face_load(
     #[cppgc] this: &Wrap<Rc<FontFace>>,
@@ -1476,6 +3319,16 @@ pub fn op_canvas_2d_font_face_load(
     data.load(source, from_url)
 }
 
+#[op2]
+pub fn op_canvas_2d_font_face_load_local(
+    state: &OpState,
+    #[cppgc] this: &Wrap<Rc<FontFace>>,
+) -> Result<(), Canvas2DError> {
+    let fonts = state.borrow::<Rc<RefCell<FontFaceSet>>>().borrow();
+    let mut data = this.data().borrow_mut();
+    data.load_local(fonts.system_fonts())
+}
+
 #[op2(fast)]
 pub fn op_canvas_2d_font_face_set_insert(
     #[cppgc] this: &Wrap<Rc<RefCell<FontFaceSet>>>,
@@ -1500,6 +3353,33 @@ pub fn op_canvas_2d_font_face_set_clear(#[cppgc] this: &Wrap<Rc<RefCell<FontFace
     this.clear()
 }
 
+#[op2]
+pub fn op_canvas_2d_font_face_set_set_generic_family(
+    #[cppgc] this: &Wrap<Rc<RefCell<FontFaceSet>>>,
+    #[string] generic: &str,
+    #[string] families: &str,
+) -> Result<(), Canvas2DError> {
+    let generic =
+        ComputedGenericFamily::from_css_string(generic).map_err(|e| Canvas2DError::ParseCss {
+            css: generic.to_owned(),
+            kind: css::ValueKind::GenericFamilyName,
+            details: css::SyntaxError::from(e),
+        })?;
+    let families = ComputedSpecificFamilyList::from_css_string(families).map_err(|e| {
+        Canvas2DError::ParseCss {
+            css: families.to_owned(),
+            kind: css::ValueKind::FontFamilyNameList,
+            details: css::SyntaxError::from(e),
+        }
+    })?;
+    let mut this = this.borrow_mut();
+    this.set_generic_family(
+        generic,
+        families.0.into_iter().map(|family| family.name.to_string()),
+    );
+    Ok(())
+}
+
 #[op2]
 pub fn op_canvas_2d_font_face_set_match<'a>(
     scope: &mut v8::PinScope<'a, '_>,
@@ -1533,6 +3413,103 @@ pub fn op_canvas_2d_font_source(state: &OpState) -> Wrap<Rc<RefCell<FontFaceSet>
     Wrap::new(state.borrow::<Rc<RefCell<FontFaceSet>>>().clone())
 }
 
-pub fn init(state: &mut OpState) {
-    state.put(Rc::new(RefCell::new(FontFaceSet::new())));
+#[op2(fast)]
+pub fn op_canvas_2d_text_layout_get_metrics(
+    #[cppgc] this: &Wrap<TextLayout>,
+    #[buffer] out: &mut [f64],
+) -> bool {
+    out[0] = this.width() as f64;
+    out[1] = this.height() as f64;
+    this.truncated()
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_text_layout_get_line_count(#[cppgc] this: &Wrap<TextLayout>) -> u32 {
+    this.lines().len() as u32
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_text_layout_get_line(
+    #[cppgc] this: &Wrap<TextLayout>,
+    index: u32,
+    #[buffer] out: &mut [f64],
+) {
+    let line = this.lines()[index as usize];
+    out[0] = line.start as f64;
+    out[1] = line.end as f64;
+    out[2] = line.x as f64;
+    out[3] = line.baseline as f64;
+    out[4] = line.width as f64;
+}
+
+#[op2]
+#[cppgc]
+pub fn op_canvas_2d_text_layout_to_path(#[cppgc] this: &Wrap<TextLayout>) -> Wrap<RefCell<Path>> {
+    Wrap::new(RefCell::new(this.path().clone()))
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_text_metrics_get_glyph_count(#[cppgc] this: &Wrap<MeasuredText>) -> u32 {
+    this.glyphs().len() as u32
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_text_metrics_get_glyph(
+    #[cppgc] this: &Wrap<MeasuredText>,
+    index: u32,
+    #[buffer] out: &mut [f64],
+) {
+    let glyph = this.glyphs()[index as usize];
+    out[0] = glyph.cluster as f64;
+    out[1] = glyph.x as f64;
+    out[2] = glyph.advance as f64;
+}
+
+#[op2]
+pub fn op_canvas_2d_text_metrics_get_selection_rects<'a>(
+    scope: &mut v8::PinScope<'a, '_>,
+    #[cppgc] this: &Wrap<MeasuredText>,
+    start: u32,
+    end: u32,
+) -> v8::Local<'a, v8::Array> {
+    let elements = this
+        .selection_rects(start as usize, end as usize)
+        .into_iter()
+        .flat_map(|rect| [rect.min.x, rect.min.y, rect.width(), rect.height()])
+        .map(|value| v8::Number::new(scope, value as f64).into())
+        .collect::<Vec<_>>();
+    v8::Array::new_with_elements(scope, &elements)
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_text_metrics_get_actual_bounding_box(
+    #[cppgc] this: &Wrap<MeasuredText>,
+    start: u32,
+    end: u32,
+    #[buffer] out: &mut [f64],
+) {
+    let rect = this.actual_bounding_box(start as usize, end as usize);
+    out[0] = rect.min.x as f64;
+    out[1] = rect.min.y as f64;
+    out[2] = rect.width() as f64;
+    out[3] = rect.height() as f64;
+}
+
+#[op2(fast)]
+pub fn op_canvas_2d_text_metrics_get_index_from_offset(
+    #[cppgc] this: &Wrap<MeasuredText>,
+    offset: f64,
+) -> u32 {
+    this.index_from_offset(offset as f32) as u32
+}
+
+pub fn init(
+    state: &mut OpState,
+    system_font_options: SystemFontOptions,
+    generic_families: GenericFontFamilies,
+) {
+    state.put(Rc::new(RefCell::new(FontFaceSet::new(
+        system_font_options,
+        generic_families,
+    ))));
 }